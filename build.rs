@@ -1,8 +1,210 @@
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::io::Write;
 
+#[path = "src/corpus/validate.rs"]
+#[allow(dead_code)]
+mod validate;
+
+#[path = "src/corpus/extract.rs"]
+#[allow(dead_code)]
+mod extract;
+
+/// Checkout of https://github.com/chinese-poetry/chinese-poetry
+const CHINESE_POETRY_DIR: &str = "POETRY_PASS_CHINESE_POETRY_DIR";
+/// Checkout of https://github.com/pwxcoo/chinese-xinhua
+const CHINESE_XINHUA_DIR: &str = "POETRY_PASS_CHINESE_XINHUA_DIR";
+
+/// Read a data file and keep only the lines that pass validation
+fn load(file: &str, content: &str) -> Vec<String> {
+    let report = validate::validate(content.lines());
+    for rejected in &report.rejected {
+        println!(
+            "cargo:warning={}:{}: skipping {:?}, no pinyin for {:?}",
+            file, rejected.line_no, rejected.line, rejected.ch
+        );
+    }
+    if !report.duplicates.is_empty() {
+        println!("cargo:warning={}: dropped {} duplicate lines", file, report.duplicates.len());
+    }
+    report.lines
+}
+
+/// Read `成语\t拼音\t释义` lines, skipping idioms that fail validation or
+/// whose pinyin does not have one syllable per character
+fn load_chengyu(file: &str, content: &str) -> Vec<(String, String, String)> {
+    let mut seen = std::collections::HashSet::new();
+    let mut idioms = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split('\t').map(str::trim);
+        let word = validate::normalize(fields.next().unwrap_or_default());
+        let pinyin = fields.next().unwrap_or_default().to_string();
+        let meaning = fields.next().unwrap_or_default().to_string();
+        
+        if let Some(ch) = validate::unromanizable(&word) {
+            println!("cargo:warning={}:{}: skipping {:?}, no pinyin for {:?}", file, i + 1, word, ch);
+        } else if pinyin.split_whitespace().count() != word.chars().count() {
+            println!("cargo:warning={}:{}: skipping {:?}, pinyin {:?} does not match", file, i + 1, word, pinyin);
+        } else if seen.insert(word.clone()) {
+            idioms.push((word, pinyin, meaning));
+        }
+    }
+    idioms
+}
+
+/// Read `句子\t拼音` lines of pinyin for lines whose polyphones the
+/// `pinyin` crate reads differently, skipping readings that do not have one
+/// syllable per character
+fn load_readings(file: &str, content: &str) -> HashMap<String, String> {
+    let mut readings = HashMap::new();
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split('\t').map(str::trim);
+        let text = validate::normalize(fields.next().unwrap_or_default());
+        let pinyin = fields.next().unwrap_or_default().to_string();
+        if pinyin.split_whitespace().count() != text.chars().count() {
+            println!("cargo:warning={}:{}: skipping {:?}, pinyin {:?} does not match", file, i + 1, text, pinyin);
+        } else {
+            readings.insert(text, pinyin);
+        }
+    }
+    readings
+}
+
+/// A line with the author and the tune or title of its text: (句子, 作者, 篇名)
+type PoemLine = (String, String, String);
+
+/// Read `作者\t篇名\t全文` lines and split each text into lines of 3-11
+/// characters, the natural lengths of 词, 曲 and classical prose
+fn load_poems(file: &str, content: &str) -> Vec<PoemLine> {
+    let options = extract::LineOptions::ci();
+    let mut lines = Vec::new();
+    for line in content.lines() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split('\t').map(str::trim);
+        let author = fields.next().unwrap_or_default();
+        let title = fields.next().unwrap_or_default();
+        let text = fields.next().unwrap_or_default();
+        for sentence in extract::poem_sentences(text, &options) {
+            lines.push((sentence.to_string(), author.to_string(), title.to_string()));
+        }
+    }
+    dedupe_poems(file, lines)
+}
+
+/// Drop lines without pinyin and lines seen before, keeping the first
+/// occurrence's author and tune
+fn dedupe_poems(file: &str, lines: Vec<PoemLine>) -> Vec<PoemLine> {
+    let mut seen = std::collections::HashSet::new();
+    let mut kept = Vec::new();
+    for line in lines {
+        if let Some(ch) = validate::unromanizable(&line.0) {
+            println!("cargo:warning={}: skipping {:?}, no pinyin for {:?}", file, line.0, ch);
+        } else if seen.insert(line.0.clone()) {
+            kept.push(line);
+        }
+    }
+    kept
+}
+
+/// How many of the most frequent characters count as common for tiers
+const COMMON_CHARS: usize = 600;
+
+/// The `COMMON_CHARS` most frequent characters of `lines`, most frequent
+/// first, ties broken by code point so the result is stable
+fn common_chars<'a, I: IntoIterator<Item = &'a str>>(lines: I) -> String {
+    let mut counts = std::collections::HashMap::new();
+    for line in lines {
+        for ch in line.chars() {
+            *counts.entry(ch).or_insert(0usize) += 1;
+        }
+    }
+    let mut chars: Vec<(char, usize)> = counts.into_iter().collect();
+    chars.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    chars.into_iter().take(COMMON_CHARS).map(|(ch, _)| ch).collect()
+}
+
+fn feature_enabled(feature: &str) -> bool {
+    let name = feature.to_uppercase().replace('-', "_");
+    env::var_os(format!("CARGO_FEATURE_{}", name)).is_some()
+}
+
+/// Dataset directory from the environment, or its default under `data/`
+fn dataset_dir(var: &str, default: &str, feature: &str) -> PathBuf {
+    println!("cargo:rerun-if-env-changed={}", var);
+    let dir = env::var_os(var)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new("data").join(default));
+    if !dir.is_dir() {
+        panic!(
+            "feature `{}` needs a checkout of the dataset at {} (set {} to use another path)",
+            feature,
+            dir.display(),
+            var
+        );
+    }
+    println!("cargo:rerun-if-changed={}", dir.display());
+    dir
+}
+
+/// Extract text from every JSON file in `dir` whose name starts with `prefix`
+fn extract_dir<T, F>(dir: &Path, prefix: &str, extract: F) -> Vec<T>
+where
+    F: Fn(&str) -> serde_json::Result<Vec<T>>,
+{
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("cannot read {}: {}", dir.display(), e))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_string_lossy();
+            name.starts_with(prefix) && name.ends_with(".json")
+        })
+        .collect();
+    files.sort();
+
+    let mut lines = Vec::new();
+    for file in files {
+        let json = fs::read_to_string(&file).unwrap();
+        lines.extend(extract(&json).unwrap_or_else(|e| panic!("{}: {}", file.display(), e)));
+    }
+    lines
+}
+
+/// Append extra lines to a bundled corpus, validating the result again
+fn extend(file: &str, lines: Vec<String>, extra: Vec<String>) -> Vec<String> {
+    let combined = lines.into_iter().chain(extra).collect::<Vec<_>>();
+    load(file, &combined.join("\n"))
+}
+
+fn write_list(f: &mut fs::File, name: &str, lines: &[String]) {
+    writeln!(f, "pub const {}: &[&str] = &[", name).unwrap();
+    for line in lines {
+        writeln!(f, "    {:?},", line).unwrap();
+    }
+    writeln!(f, "];").unwrap();
+}
+
+/// Write poem lines with their reading from `readings`, or "" to use the
+/// `pinyin` crate's
+fn write_poems(f: &mut fs::File, name: &str, lines: &[PoemLine], readings: &HashMap<String, String>) {
+    writeln!(f, "/// (句子, 作者, 篇名, 拼音)").unwrap();
+    writeln!(f, "pub const {}: &[(&str, &str, &str, &str)] = &[", name).unwrap();
+    for (line, author, title) in lines {
+        let pinyin = readings.get(line).map_or("", String::as_str);
+        writeln!(f, "    ({:?}, {:?}, {:?}, {:?}),", line, author, title, pinyin).unwrap();
+    }
+    writeln!(f, "];").unwrap();
+}
+
 fn main() {
     println!("cargo:rerun-if-changed=data/");
     
@@ -13,29 +215,141 @@ fn main() {
     let poetry_content = fs::read_to_string("data/poetry.txt")
         .unwrap_or_else(|_| include_str!("data/poetry.txt").to_string());
     
-    let poetry_lines: Vec<&str> = poetry_content
-        .lines()
-        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
-        .collect();
+    let mut poetry_lines = load("data/poetry.txt", &poetry_content);
     
     let words_content = fs::read_to_string("data/words.txt")
         .unwrap_or_else(|_| include_str!("data/words.txt").to_string());
     
-    let words_lines: Vec<&str> = words_content
-        .lines()
-        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+    let words_lines = load("data/words.txt", &words_content);
+    
+    let chengyu_content = fs::read_to_string("data/chengyu.txt")
+        .unwrap_or_else(|_| include_str!("data/chengyu.txt").to_string());
+    
+    let mut chengyu = load_chengyu("data/chengyu.txt", &chengyu_content);
+    
+    let songci_content = fs::read_to_string("data/songci.txt")
+        .unwrap_or_else(|_| include_str!("data/songci.txt").to_string());
+    
+    let mut song_ci = load_poems("data/songci.txt", &songci_content);
+    
+    let yuanqu_content = fs::read_to_string("data/yuanqu.txt")
+        .unwrap_or_else(|_| include_str!("data/yuanqu.txt").to_string());
+    
+    let yuan_qu = load_poems("data/yuanqu.txt", &yuanqu_content);
+    
+    let lunyu_content = fs::read_to_string("data/lunyu.txt")
+        .unwrap_or_else(|_| include_str!("data/lunyu.txt").to_string());
+    
+    let lun_yu = load_poems("data/lunyu.txt", &lunyu_content);
+    
+    let daodejing_content = fs::read_to_string("data/daodejing.txt")
+        .unwrap_or_else(|_| include_str!("data/daodejing.txt").to_string());
+    
+    let dao_de_jing = load_poems("data/daodejing.txt", &daodejing_content);
+    
+    let shijing_content = fs::read_to_string("data/shijing.txt")
+        .unwrap_or_else(|_| include_str!("data/shijing.txt").to_string());
+    
+    let shi_jing = load_poems("data/shijing.txt", &shijing_content);
+    
+    let guwen_content = fs::read_to_string("data/guwen.txt")
+        .unwrap_or_else(|_| include_str!("data/guwen.txt").to_string());
+    
+    let gu_wen = load_poems("data/guwen.txt", &guwen_content);
+    
+    let famous_content = fs::read_to_string("data/famous.txt")
+        .unwrap_or_else(|_| include_str!("data/famous.txt").to_string());
+    
+    let famous = load("data/famous.txt", &famous_content);
+    
+    let readings_content = fs::read_to_string("data/readings.txt")
+        .unwrap_or_else(|_| include_str!("data/readings.txt").to_string());
+    
+    let readings = load_readings("data/readings.txt", &readings_content);
+    let quotations: HashSet<&str> = [&lun_yu, &dao_de_jing, &shi_jing, &gu_wen]
+        .into_iter()
+        .flatten()
+        .map(|(line, _, _)| line.as_str())
         .collect();
+    for line in readings.keys().filter(|line| !quotations.contains(line.as_str())) {
+        println!("cargo:warning=data/readings.txt: {:?} is not a line of the quotation corpora", line);
+    }
     
-    writeln!(f, "/// Auto generated from data files").unwrap();
-    writeln!(f, "pub const POETRY: &[&str] = &[").unwrap();
-    for line in poetry_lines {
-        writeln!(f, "    \"{}\",", line.trim()).unwrap();
+    // Character frequencies come from the bundled texts only, so enabling a
+    // corpus feature does not move lines between tiers
+    let common = common_chars(
+        poetry_lines.iter().map(String::as_str).chain(
+            [&song_ci, &yuan_qu, &lun_yu, &dao_de_jing, &shi_jing, &gu_wen]
+                .into_iter()
+                .flatten()
+                .map(|(line, _, _)| line.as_str()),
+        ),
+    );
+    
+    let line_options = extract::LineOptions::default();
+    
+    if feature_enabled("corpus-full-tang") {
+        let dir = dataset_dir(CHINESE_POETRY_DIR, "chinese-poetry", "corpus-full-tang");
+        let extra = extract_dir(&dir.join("全唐诗"), "poet.tang.", |json| {
+            extract::poetry_lines(json, &line_options)
+        });
+        poetry_lines = extend("全唐诗", poetry_lines, extra);
     }
-    writeln!(f, "];").unwrap();
     
-    writeln!(f, "\npub const WORDS: &[&str] = &[").unwrap();
-    for line in words_lines {
-        writeln!(f, "    \"{}\",", line.trim()).unwrap();
+    if feature_enabled("corpus-song-ci") {
+        let dir = dataset_dir(CHINESE_POETRY_DIR, "chinese-poetry", "corpus-song-ci");
+        let extra = extract_dir(&dir.join("宋词"), "ci.song.", |json| {
+            extract::poem_lines(json, &extract::LineOptions::ci())
+        });
+        let extra = extra.into_iter().map(|line| {
+            (line.text, line.author.unwrap_or_default(), line.title.unwrap_or_default())
+        });
+        song_ci = dedupe_poems("宋词", song_ci.into_iter().chain(extra).collect());
+    }
+    
+    if feature_enabled("corpus-chengyu") {
+        let dir = dataset_dir(CHINESE_XINHUA_DIR, "chinese-xinhua", "corpus-chengyu");
+        let file = dir.join("data").join("idiom.json");
+        let json = fs::read_to_string(&file)
+            .unwrap_or_else(|e| panic!("cannot read {}: {}", file.display(), e));
+        let idioms = extract::idioms(&json)
+            .unwrap_or_else(|e| panic!("{}: {}", file.display(), e));
+        let lines: Vec<String> = chengyu.iter()
+            .map(|(word, pinyin, meaning)| format!("{}\t{}\t{}", word, pinyin, meaning))
+            .chain(idioms.into_iter().map(|idiom| {
+                format!("{}\t{}\t{}", idiom.word, idiom.pinyin, idiom.explanation)
+            }))
+            .collect();
+        chengyu = load_chengyu("idiom.json", &lines.join("\n"));
+    }
+    
+    writeln!(f, "/// Auto generated from data files").unwrap();
+    write_list(&mut f, "POETRY", &poetry_lines);
+    writeln!(f).unwrap();
+    write_list(&mut f, "WORDS", &words_lines);
+    
+    writeln!(f, "\n/// (成语, 拼音, 释义)").unwrap();
+    writeln!(f, "pub const CHENGYU: &[(&str, &str, &str)] = &[").unwrap();
+    for (word, pinyin, meaning) in chengyu {
+        writeln!(f, "    ({:?}, {:?}, {:?}),", word, pinyin, meaning).unwrap();
     }
     writeln!(f, "];").unwrap();
-}
\ No newline at end of file
+    
+    writeln!(f).unwrap();
+    write_poems(&mut f, "SONG_CI", &song_ci, &readings);
+    writeln!(f).unwrap();
+    write_poems(&mut f, "YUAN_QU", &yuan_qu, &readings);
+    writeln!(f).unwrap();
+    write_poems(&mut f, "LUN_YU", &lun_yu, &readings);
+    writeln!(f).unwrap();
+    write_poems(&mut f, "DAO_DE_JING", &dao_de_jing, &readings);
+    writeln!(f).unwrap();
+    write_poems(&mut f, "SHI_JING", &shi_jing, &readings);
+    writeln!(f).unwrap();
+    write_poems(&mut f, "GU_WEN", &gu_wen, &readings);
+    
+    writeln!(f, "\n/// Famous lines from school curricula, the `Famous` tier").unwrap();
+    write_list(&mut f, "FAMOUS", &famous);
+    writeln!(f, "\n/// The {} most frequent characters of the bundled texts", COMMON_CHARS).unwrap();
+    writeln!(f, "pub const COMMON_CHARS: &str = {:?};", common).unwrap();
+}
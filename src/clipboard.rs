@@ -0,0 +1,152 @@
+//! Clipboard access through the local command line tools
+//!
+//! Wayland sessions use `wl-copy` and `wl-paste` from wl-clipboard, X11
+//! sessions `xclip` or `xsel`, whichever is installed. Clearing happens in a
+//! detached copy of this program, so the terminal is free while it waits.
+
+use std::env;
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+/// Hidden subcommand of the detached process that clears the clipboard
+pub const CLEAR_COMMAND: &str = "__clear-clipboard";
+
+/// A command line clipboard tool
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    WlClipboard,
+    Xclip,
+    Xsel,
+}
+
+impl Backend {
+    /// In the order they are tried
+    const ALL: [Backend; 3] = [Backend::WlClipboard, Backend::Xclip, Backend::Xsel];
+
+    fn name(self) -> &'static str {
+        match self {
+            Backend::WlClipboard => "wl-clipboard",
+            Backend::Xclip => "xclip",
+            Backend::Xsel => "xsel",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        Backend::ALL.into_iter().find(|backend| backend.name() == name)
+    }
+
+    /// Whether the session has the display server this tool talks to
+    fn has_display(self) -> bool {
+        let variable = match self {
+            Backend::WlClipboard => "WAYLAND_DISPLAY",
+            Backend::Xclip | Backend::Xsel => "DISPLAY",
+        };
+        env::var_os(variable).is_some_and(|display| !display.is_empty())
+    }
+
+    fn copy_command(self) -> Command {
+        match self {
+            Backend::WlClipboard => tool("wl-copy", &[]),
+            Backend::Xclip => tool("xclip", &["-selection", "clipboard", "-in"]),
+            Backend::Xsel => tool("xsel", &["--clipboard", "--input"]),
+        }
+    }
+
+    fn paste_command(self) -> Command {
+        match self {
+            Backend::WlClipboard => tool("wl-paste", &["--no-newline"]),
+            Backend::Xclip => tool("xclip", &["-selection", "clipboard", "-out"]),
+            Backend::Xsel => tool("xsel", &["--clipboard", "--output"]),
+        }
+    }
+
+    fn clear_command(self) -> Command {
+        match self {
+            Backend::WlClipboard => tool("wl-copy", &["--clear"]),
+            // Copying nothing leaves the clipboard empty
+            Backend::Xclip => self.copy_command(),
+            Backend::Xsel => tool("xsel", &["--clipboard", "--clear"]),
+        }
+    }
+}
+
+fn tool(program: &str, args: &[&str]) -> Command {
+    let mut command = Command::new(program);
+    command.args(args);
+    command
+}
+
+/// Put `text` on the clipboard with the first tool that works
+pub fn copy(text: &str) -> io::Result<Backend> {
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, "wl-copy, xclip or xsel with a running display");
+    for backend in Backend::ALL.into_iter().filter(|backend| backend.has_display()) {
+        match run(backend.copy_command(), text) {
+            Ok(()) => return Ok(backend),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+fn paste(backend: Backend) -> io::Result<String> {
+    let output = backend.paste_command().stderr(Stdio::null()).output()?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Run a tool with `input` on its standard input
+///
+/// The output is discarded rather than read: `xclip` and `wl-copy` leave a
+/// process behind that serves the clipboard and would keep a pipe open.
+fn run(mut command: Command, input: &str) -> io::Result<()> {
+    let mut child = command.stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null()).spawn()?;
+    child.stdin.take().expect("stdin is piped").write_all(input.as_bytes())?;
+    let status = child.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{:?} failed: {}", command.get_program(), status)))
+    }
+}
+
+/// Clear the clipboard after `seconds` from a detached process
+///
+/// The text goes through a pipe, not the command line, so it does not show
+/// up in the process list. The process has its own process group and
+/// survives the terminal being closed.
+pub fn clear_later(backend: Backend, text: &str, seconds: u64) -> io::Result<()> {
+    let mut command = Command::new(env::current_exe()?);
+    command
+        .args([CLEAR_COMMAND, backend.name(), &seconds.to_string()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let mut child = command.spawn()?;
+    child.stdin.take().expect("stdin is piped").write_all(text.as_bytes())
+}
+
+/// The detached process: wait, then clear the clipboard unless something
+/// else has been copied meanwhile
+pub fn run_clear(args: &[String]) -> i32 {
+    let (Some(backend), Some(seconds)) = (
+        args.first().and_then(|name| Backend::parse(name)),
+        args.get(1).and_then(|seconds| seconds.parse().ok()),
+    ) else {
+        return 2;
+    };
+    let mut text = String::new();
+    if io::stdin().read_to_string(&mut text).is_err() {
+        return 1;
+    }
+
+    thread::sleep(Duration::from_secs(seconds));
+    let replaced = paste(backend).is_ok_and(|current| current.trim_end_matches('\n') != text);
+    if replaced || run(backend.clear_command(), "").is_ok() {
+        0
+    } else {
+        1
+    }
+}
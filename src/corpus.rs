@@ -0,0 +1,379 @@
+#[cfg(feature = "import")]
+pub mod extract;
+pub mod validate;
+
+use rand::seq::IndexedRandom;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+/// A corpus entry and what is known about where it comes from
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Entry {
+    pub text: String,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub author: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub title: Option<String>,
+    /// Tone-marked pinyin separated by spaces, used instead of the `pinyin`
+    /// crate's reading when present
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub pinyin: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub meaning: Option<String>,
+    /// How well known the text is, `None` for entries of custom corpora
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub tier: Option<Tier>,
+}
+
+/// How familiar a line is, from best to least known
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Tier {
+    /// Listed in `data/famous.txt`: lines learned by heart at school
+    Famous,
+    /// Made only of the most frequent characters of the bundled texts
+    Common,
+    /// Everything else
+    Obscure,
+}
+
+impl Tier {
+    pub const ALL: [Tier; 3] = [Tier::Famous, Tier::Common, Tier::Obscure];
+
+    /// The tier of a line of text, from the bundled famous lines and
+    /// character frequencies
+    pub fn of(text: &str) -> Tier {
+        static FAMOUS: OnceLock<HashSet<&str>> = OnceLock::new();
+        let famous = FAMOUS.get_or_init(|| crate::FAMOUS.iter().copied().collect());
+        if famous.contains(text) {
+            Tier::Famous
+        } else if text.chars().all(|ch| crate::COMMON_CHARS.contains(ch)) {
+            Tier::Common
+        } else {
+            Tier::Obscure
+        }
+    }
+}
+
+impl Entry {
+    pub fn new(text: &str) -> Self {
+        Entry {
+            text: text.to_string(),
+            ..Entry::default()
+        }
+    }
+
+    /// Length of the text in characters
+    pub fn char_count(&self) -> usize {
+        self.text.chars().count()
+    }
+}
+
+/// A list of candidate lines that password segments are drawn from
+///
+/// Corpora are cheap to clone: the entries are shared between clones.
+#[derive(Clone, Debug)]
+pub struct Corpus {
+    name: String,
+    entries: Arc<[Entry]>,
+}
+
+impl Corpus {
+    /// Build a corpus from in-memory lines
+    ///
+    /// Lines are trimmed, and blank lines and lines starting with `#` are
+    /// skipped, the same rules `build.rs` applies to the bundled data files.
+    pub fn from_lines<I, S>(name: &str, lines: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let entries = lines
+            .into_iter()
+            .filter_map(|line| {
+                let line = line.as_ref();
+                if line.trim().is_empty() || line.starts_with('#') {
+                    None
+                } else {
+                    Some(Entry::new(line.trim()))
+                }
+            });
+        Self::from_entries(name, entries)
+    }
+
+    /// Build a corpus from entries that may carry author and title
+    pub fn from_entries<I: IntoIterator<Item = Entry>>(name: &str, entries: I) -> Self {
+        Corpus {
+            name: name.to_string(),
+            entries: entries.into_iter().collect(),
+        }
+    }
+
+    /// Build a corpus from the contents of a text file, one entry per line
+    pub fn from_text(name: &str, text: &str) -> Self {
+        Self::from_lines(name, text.lines())
+    }
+
+    /// Load a corpus from a text file; the corpus is named after the file stem
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        Ok(Self::from_text(&name_from_path(path), &text))
+    }
+
+    /// Load every `.txt` file in a directory into a single corpus
+    ///
+    /// With the `import` feature, `.json` files in the chinese-poetry format
+    /// are loaded as well. Files are read in file name order so the entry
+    /// order is stable.
+    pub fn from_dir<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut entries = Vec::new();
+        for file in files_in(path)? {
+            match file.extension().and_then(|ext| ext.to_str()) {
+                Some("txt") => {
+                    let text = fs::read_to_string(&file)?;
+                    entries.extend(Self::from_text("", &text).entries.iter().cloned());
+                }
+                #[cfg(feature = "import")]
+                Some("json") => {
+                    entries.extend(Self::from_chinese_poetry(&file)?.entries.iter().cloned());
+                }
+                _ => {}
+            }
+        }
+        Ok(Self::from_entries(&name_from_path(path), entries))
+    }
+
+    /// Load poem lines from a chinese-poetry JSON file, or every `.json`
+    /// file in a directory, keeping each line's author and title
+    ///
+    /// Lines are extracted like `process.py` does: 5-7 character sentences
+    /// of common CJK characters. Use [`extract::poem_lines`] for other limits.
+    #[cfg(feature = "import")]
+    pub fn from_chinese_poetry<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        let files = if path.is_dir() {
+            files_in(path)?
+                .into_iter()
+                .filter(|file| file.extension().is_some_and(|ext| ext == "json"))
+                .collect()
+        } else {
+            vec![path.to_path_buf()]
+        };
+
+        let options = extract::LineOptions::default();
+        let mut entries = Vec::new();
+        for file in files {
+            let json = fs::read_to_string(&file)?;
+            let lines = extract::poem_lines(&json, &options)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", file.display(), e)))?;
+            entries.extend(lines.into_iter().map(|line| Entry {
+                text: line.text,
+                author: line.author,
+                title: line.title,
+                ..Entry::default()
+            }));
+        }
+        Ok(Self::from_entries(&name_from_path(path), entries))
+    }
+
+    /// Load a corpus from a text file, a chinese-poetry JSON file (with the
+    /// `import` feature) or a directory of such files
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        if path.is_dir() {
+            return Self::from_dir(path);
+        }
+        #[cfg(feature = "import")]
+        if path.extension().is_some_and(|ext| ext == "json") {
+            return Self::from_chinese_poetry(path);
+        }
+        Self::from_file(path)
+    }
+
+    /// The built-in poetry corpus (`POETRY`)
+    pub fn poetry() -> &'static Corpus {
+        static POETRY: OnceLock<Corpus> = OnceLock::new();
+        POETRY.get_or_init(|| Corpus::from_lines("poetry", crate::POETRY).with_tiers(Tier::of))
+    }
+
+    /// The built-in words corpus (`WORDS`)
+    ///
+    /// Everyday words are at least `Common`, whatever their characters.
+    pub fn words() -> &'static Corpus {
+        static WORDS: OnceLock<Corpus> = OnceLock::new();
+        WORDS.get_or_init(|| {
+            Corpus::from_lines("words", crate::WORDS).with_tiers(|text| Tier::of(text).min(Tier::Common))
+        })
+    }
+
+    /// The built-in idiom corpus (`CHENGYU`), with pinyin and meanings
+    ///
+    /// Like words, idioms are at least `Common`.
+    pub fn chengyu() -> &'static Corpus {
+        static CHENGYU: OnceLock<Corpus> = OnceLock::new();
+        CHENGYU.get_or_init(|| {
+            let entries = crate::CHENGYU.iter().map(|&(word, pinyin, meaning)| Entry {
+                text: word.to_string(),
+                pinyin: Some(pinyin.to_string()),
+                meaning: Some(meaning.to_string()),
+                tier: Some(Tier::of(word).min(Tier::Common)),
+                ..Entry::default()
+            });
+            Corpus::from_entries("chengyu", entries)
+        })
+    }
+
+    /// The built-in 宋词 corpus (`SONG_CI`), lines of 3-11 characters with
+    /// author and tune
+    pub fn song_ci() -> &'static Corpus {
+        static SONG_CI: OnceLock<Corpus> = OnceLock::new();
+        SONG_CI.get_or_init(|| Corpus::from_poem_lines("song_ci", crate::SONG_CI))
+    }
+
+    /// The built-in 元曲 corpus (`YUAN_QU`), lines of 3-11 characters with
+    /// author and tune
+    pub fn yuan_qu() -> &'static Corpus {
+        static YUAN_QU: OnceLock<Corpus> = OnceLock::new();
+        YUAN_QU.get_or_init(|| Corpus::from_poem_lines("yuan_qu", crate::YUAN_QU))
+    }
+
+    /// The built-in 论语 quotations (`LUN_YU`), titled by chapter
+    pub fn lun_yu() -> &'static Corpus {
+        static LUN_YU: OnceLock<Corpus> = OnceLock::new();
+        LUN_YU.get_or_init(|| Corpus::from_poem_lines("lun_yu", crate::LUN_YU))
+    }
+
+    /// The built-in 道德经 quotations (`DAO_DE_JING`), titled by chapter
+    pub fn dao_de_jing() -> &'static Corpus {
+        static DAO_DE_JING: OnceLock<Corpus> = OnceLock::new();
+        DAO_DE_JING.get_or_init(|| Corpus::from_poem_lines("dao_de_jing", crate::DAO_DE_JING))
+    }
+
+    /// The built-in 诗经 lines (`SHI_JING`), with section and title
+    pub fn shi_jing() -> &'static Corpus {
+        static SHI_JING: OnceLock<Corpus> = OnceLock::new();
+        SHI_JING.get_or_init(|| Corpus::from_poem_lines("shi_jing", crate::SHI_JING))
+    }
+
+    /// The built-in classical prose quotations (`GU_WEN`), mostly from
+    /// 古文观止, with author and title
+    pub fn gu_wen() -> &'static Corpus {
+        static GU_WEN: OnceLock<Corpus> = OnceLock::new();
+        GU_WEN.get_or_init(|| Corpus::from_poem_lines("gu_wen", crate::GU_WEN))
+    }
+
+    fn from_poem_lines(name: &str, lines: &[(&str, &str, &str, &str)]) -> Corpus {
+        let entries = lines.iter().map(|&(line, author, title, pinyin)| Entry {
+            text: line.to_string(),
+            author: Some(author.to_string()),
+            title: Some(title.to_string()),
+            pinyin: Some(pinyin.to_string()).filter(|pinyin| !pinyin.is_empty()),
+            tier: Some(Tier::of(line)),
+            ..Entry::default()
+        });
+        Corpus::from_entries(name, entries)
+    }
+
+    /// Tag every entry with the tier `tier_of` gives for its text
+    pub fn with_tiers<F: Fn(&str) -> Tier>(self, tier_of: F) -> Corpus {
+        let entries = self.entries.iter().map(|entry| Entry {
+            tier: Some(tier_of(&entry.text)),
+            ..entry.clone()
+        });
+        Corpus::from_entries(&self.name, entries)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entropy in bits of picking one entry uniformly, `log2(len)`
+    pub fn entropy_bits(&self) -> f64 {
+        (self.len() as f64).log2()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.entries.get(index).map(|entry| entry.text.as_str())
+    }
+
+    pub fn entry(&self, index: usize) -> Option<&Entry> {
+        self.entries.get(index)
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.text.as_str())
+    }
+
+    /// Pick a random entry
+    pub fn choose(&self) -> Option<&Entry> {
+        let mut rng = rand::rng();
+        self.entries.choose(&mut rng)
+    }
+}
+
+/// A corpus serializes as its name and entries, e.g.
+/// `{"name": "mine", "entries": [{"text": "明月"}]}`
+#[cfg(feature = "serde")]
+impl serde::Serialize for Corpus {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("Corpus", 2)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("entries", &*self.entries)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Corpus {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Fields {
+            name: String,
+            entries: Vec<Entry>,
+        }
+
+        let fields = Fields::deserialize(deserializer)?;
+        Ok(Corpus::from_entries(&fields.name, fields.entries))
+    }
+}
+
+/// Files directly inside `dir`, sorted by name
+fn files_in(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let file = entry?.path();
+        if file.is_file() {
+            files.push(file);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn name_from_path(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "custom".to_string())
+}
@@ -0,0 +1,192 @@
+//! Corpus extraction from raw JSON datasets, ported from `process.py`
+//!
+//! Like `validate`, this module only depends on `std`, `serde` and
+//! `serde_json` so that `build.rs` can include it with `#[path]`.
+
+use serde::Deserialize;
+use std::collections::HashSet;
+
+/// Punctuation that ends a sentence inside a poem paragraph
+pub const SENTENCE_DELIMITERS: &[char] = &['，', '。', '！', '？', '；'];
+
+/// A poem in the chinese-poetry JSON format
+///
+/// 宋词 name the tune (`rhythmic`) instead of a title and 诗经 keep the text
+/// in `content`; both are accepted.
+#[derive(Deserialize)]
+struct Poem {
+    #[serde(default, alias = "content")]
+    paragraphs: Vec<String>,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default, alias = "rhythmic")]
+    title: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct HskEntry {
+    #[serde(default)]
+    simplified: String,
+    #[serde(default)]
+    level: Vec<String>,
+}
+
+/// An idiom of chinese-xinhua's `idiom.json`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Idiom {
+    #[serde(default)]
+    pub word: String,
+    /// Tone-marked pinyin separated by spaces, e.g. `yī fān fēng shùn`
+    #[serde(default)]
+    pub pinyin: String,
+    #[serde(default)]
+    pub explanation: String,
+}
+
+/// A poem line together with the poem it was taken from
+#[derive(Clone, Debug, PartialEq)]
+pub struct Line {
+    pub text: String,
+    pub author: Option<String>,
+    pub title: Option<String>,
+}
+
+/// Limits for poetry line extraction
+#[derive(Clone, Debug)]
+pub struct LineOptions {
+    pub min_chars: usize,
+    pub max_chars: usize,
+}
+
+impl Default for LineOptions {
+    fn default() -> Self {
+        // 5-7 characters are the most suitable lines
+        LineOptions { min_chars: 5, max_chars: 7 }
+    }
+}
+
+impl LineOptions {
+    /// 3-11 characters, the natural line lengths of 词 and 曲
+    pub fn ci() -> Self {
+        LineOptions { min_chars: 3, max_chars: 11 }
+    }
+}
+
+/// Limits for HSK word extraction
+#[derive(Clone, Debug)]
+pub struct WordOptions {
+    pub min_chars: usize,
+    pub max_chars: usize,
+    /// Highest HSK level (`new-1` .. `new-N`) to include
+    pub max_level: u32,
+}
+
+impl Default for WordOptions {
+    fn default() -> Self {
+        WordOptions { min_chars: 2, max_chars: 4, max_level: 3 }
+    }
+}
+
+/// Extract poetry lines from a JSON array of poems with `paragraphs`
+///
+/// Paragraphs are split into sentences on `，。！？；`; sentences within the
+/// length limits made only of CJK unified ideographs are kept. The result is
+/// deduplicated and sorted.
+pub fn poetry_lines(json: &str, options: &LineOptions) -> serde_json::Result<Vec<String>> {
+    let lines = poem_lines(json, options)?;
+    Ok(lines.into_iter().map(|line| line.text).collect())
+}
+
+/// Extract poetry lines like [`poetry_lines`], keeping each line's author
+/// and title
+///
+/// A line that appears in several poems keeps the metadata of the first.
+pub fn poem_lines(json: &str, options: &LineOptions) -> serde_json::Result<Vec<Line>> {
+    let poems: Vec<Poem> = serde_json::from_str(json)?;
+    let mut seen = HashSet::new();
+    let mut lines = Vec::new();
+
+    for poem in &poems {
+        let sentences = poem.paragraphs.iter()
+            .flat_map(|paragraph| poem_sentences(paragraph, options));
+        for sentence in sentences {
+            if seen.insert(sentence) {
+                lines.push(Line {
+                    text: sentence.to_string(),
+                    author: poem.author.clone(),
+                    title: poem.title.clone(),
+                });
+            }
+        }
+    }
+
+    lines.sort_by(|a, b| a.text.cmp(&b.text));
+    Ok(lines)
+}
+
+/// Extract words from an HSK vocabulary JSON array
+///
+/// Words are kept when one of their levels is `new-1` up to
+/// `new-{max_level}` and their length is within the limits. The result is
+/// deduplicated and sorted.
+pub fn hsk_words(json: &str, options: &WordOptions) -> serde_json::Result<Vec<String>> {
+    let entries: Vec<HskEntry> = serde_json::from_str(json)?;
+    let words = entries
+        .into_iter()
+        .filter(|entry| {
+            entry.level.iter().any(|level| {
+                (1..=options.max_level).any(|i| level.contains(&format!("new-{}", i)))
+            })
+        })
+        .map(|entry| entry.simplified)
+        .filter(|word| in_range(word, options.min_chars, options.max_chars));
+    Ok(dedupe(words))
+}
+
+/// Extract four-character idioms from chinese-xinhua's `idiom.json`
+///
+/// The result is deduplicated and sorted by word.
+pub fn idioms(json: &str) -> serde_json::Result<Vec<Idiom>> {
+    let idioms: Vec<Idiom> = serde_json::from_str(json)?;
+    let mut seen = HashSet::new();
+    let mut idioms: Vec<Idiom> = idioms
+        .into_iter()
+        .filter(|idiom| in_range(&idiom.word, 4, 4) && is_common(&idiom.word))
+        .filter(|idiom| seen.insert(idiom.word.clone()))
+        .collect();
+    idioms.sort_by(|a, b| a.word.cmp(&b.word));
+    Ok(idioms)
+}
+
+/// Split a paragraph into trimmed, non-empty sentences
+pub fn sentences(paragraph: &str) -> impl Iterator<Item = &str> {
+    paragraph
+        .split(SENTENCE_DELIMITERS)
+        .map(str::trim)
+        .filter(|sentence| !sentence.is_empty())
+}
+
+/// Sentences of a poem's text within the length limits, made only of CJK
+/// unified ideographs
+pub fn poem_sentences<'a>(text: &'a str, options: &LineOptions) -> impl Iterator<Item = &'a str> {
+    let (min, max) = (options.min_chars, options.max_chars);
+    sentences(text)
+        .filter(move |sentence| in_range(sentence, min, max))
+        .filter(|sentence| is_common(sentence))
+}
+
+/// Whether every character is a common CJK unified ideograph (U+4E00..U+9FFF)
+pub fn is_common(text: &str) -> bool {
+    text.chars().all(|c| ('\u{4e00}'..='\u{9fff}').contains(&c))
+}
+
+fn in_range(text: &str, min: usize, max: usize) -> bool {
+    (min..=max).contains(&text.chars().count())
+}
+
+fn dedupe<I: Iterator<Item = String>>(items: I) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut items: Vec<String> = items.filter(|item| seen.insert(item.clone())).collect();
+    items.sort();
+    items
+}
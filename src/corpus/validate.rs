@@ -0,0 +1,141 @@
+//! Corpus validation and normalization
+//!
+//! This module only depends on `std` and `pinyin` so that `build.rs` can
+//! include it with `#[path]` and check the bundled data files.
+
+use pinyin::ToPinyin as _;
+use std::collections::{HashMap, HashSet};
+
+/// A line that was dropped because it contains a character without pinyin
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rejected {
+    pub line_no: usize,
+    pub line: String,
+    pub ch: char,
+}
+
+/// Lines that share the same pinyin initials
+#[derive(Clone, Debug, PartialEq)]
+pub struct Collision {
+    pub initials: String,
+    pub lines: Vec<String>,
+}
+
+/// Result of validating a corpus
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    /// Normalized, deduplicated lines in their original order
+    pub lines: Vec<String>,
+    /// Lines dropped because an earlier line normalized to the same text
+    pub duplicates: Vec<String>,
+    pub rejected: Vec<Rejected>,
+    pub collisions: Vec<Collision>,
+}
+
+impl Report {
+    /// Whether every line was kept unchanged
+    pub fn is_clean(&self) -> bool {
+        self.duplicates.is_empty() && self.rejected.is_empty()
+    }
+}
+
+/// Validate corpus lines
+///
+/// Blank lines and `#` comments are skipped like in `build.rs`; every other
+/// line is normalized, deduplicated and checked for characters the `pinyin`
+/// crate cannot romanize.
+pub fn validate<'a, I>(lines: I) -> Report
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut report = Report::default();
+    let mut seen = HashSet::new();
+
+    for (i, raw) in lines.into_iter().enumerate() {
+        if raw.trim().is_empty() || raw.starts_with('#') {
+            continue;
+        }
+
+        let line = normalize(raw);
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(ch) = unromanizable(&line) {
+            report.rejected.push(Rejected { line_no: i + 1, line, ch });
+            continue;
+        }
+        if seen.insert(line.clone()) {
+            report.lines.push(line);
+        } else {
+            report.duplicates.push(line);
+        }
+    }
+
+    report.collisions = collisions(&report.lines);
+    report
+}
+
+/// Normalize a line
+///
+/// Full-width ASCII forms become half-width, all whitespace is removed, and
+/// punctuation around the line (e.g. a trailing `。`) is trimmed.
+pub fn normalize(line: &str) -> String {
+    let normalized: String = line
+        .chars()
+        .map(|c| match c {
+            '\u{3000}' => ' ',
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            _ => c,
+        })
+        .filter(|c| !c.is_whitespace())
+        .collect();
+
+    normalized
+        .trim_matches(|c: char| c.is_ascii_punctuation() || is_cjk_punctuation(c))
+        .to_string()
+}
+
+/// The first character of `text` the `pinyin` crate cannot romanize, if any
+pub fn unromanizable(text: &str) -> Option<char> {
+    text.chars()
+        .zip(text.to_pinyin())
+        .find(|(_, py)| py.is_none())
+        .map(|(c, _)| c)
+}
+
+/// Pinyin initials of `text`, matching `Mode::PinyinInit`
+pub fn initials(text: &str) -> String {
+    text.to_pinyin()
+        .filter_map(|p| p.map(|py| py.plain().chars().next().unwrap_or('?')))
+        .collect()
+}
+
+/// Group lines whose initials are identical
+pub fn collisions(lines: &[String]) -> Vec<Collision> {
+    let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+    let mut order = Vec::new();
+    for line in lines {
+        let key = initials(line);
+        let group = groups.entry(key.clone()).or_default();
+        if group.is_empty() {
+            order.push(key);
+        }
+        group.push(line.clone());
+    }
+
+    order
+        .into_iter()
+        .filter_map(|initials| {
+            let lines = groups.remove(&initials)?;
+            if lines.len() > 1 {
+                Some(Collision { initials, lines })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn is_cjk_punctuation(c: char) -> bool {
+    matches!(c, '\u{3001}'..='\u{3003}' | '\u{3008}'..='\u{3011}' | '\u{2018}'..='\u{201D}' | '\u{2026}' | '\u{00B7}')
+}
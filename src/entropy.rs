@@ -0,0 +1,177 @@
+use crate::corpus::Entry;
+use crate::transform::{transform_entry, Mode};
+use std::collections::HashMap;
+
+/// Entropy of the 4-digit number (1000-9999)
+pub const NUMBER_BITS: f64 = 13.135709286104401; // log2(9000)
+
+/// Shannon entropy in bits of one segment
+///
+/// The segment is drawn from `candidates`, every entry with its probability
+/// as given by `Source::front_candidates` or `Source::back_candidates`, and
+/// rendered in `mode`. Entries that render to the same text (homophones in
+/// pinyin, shared initials) are counted once, as an attacker who knows the
+/// corpora and settings only has to guess the rendered text.
+pub fn segment_bits(candidates: &[(&Entry, f64)], mode: &Mode) -> f64 {
+    shannon(&rendered(candidates, mode))
+}
+
+/// Shannon entropy in bits of the back segment when it is drawn again
+/// until it is distinct from the front segment
+///
+/// A back entry is rejected when it has the same text or rendered form as
+/// the front entry or shares more than half the characters of the shorter
+/// one, as [`shares_most_characters`] tells. For every front entry the back
+/// distribution is renormalized over the entries it accepts; this is the
+/// average of their entropies. Front entries that render the same may
+/// accept different back entries, and an attacker only sees the rendered
+/// front, so the true value can be somewhat higher, never lower.
+pub fn distinct_back_bits(
+    front: &[(&Entry, f64)],
+    front_mode: &Mode,
+    back: &[(&Entry, f64)],
+    back_mode: &Mode,
+) -> f64 {
+    let forms: Vec<String> = back.iter().map(|&(entry, _)| transform_entry(entry, back_mode)).collect();
+    let mut masses: HashMap<&str, f64> = HashMap::new();
+    for (&(_, p), form) in back.iter().zip(&forms) {
+        *masses.entry(form).or_default() += p;
+    }
+    let bits: f64 = masses.values().map(|&q| surprisal(q)).sum();
+    
+    let mut rejections = Rejections::new(back, &forms);
+    front
+        .iter()
+        .map(|&(entry, p)| {
+            let mut removed: HashMap<&str, f64> = HashMap::new();
+            for j in rejections.of(entry, &transform_entry(entry, front_mode)) {
+                *removed.entry(&forms[j]).or_default() += back[j].1;
+            }
+            let total: f64 = removed.values().sum();
+            if total >= 1.0 - 1e-12 {
+                // No back segment can follow this front segment
+                return 0.0;
+            }
+            // Entropy of the back distribution renormalized without the
+            // rejected entries
+            let left: f64 = removed
+                .iter()
+                .map(|(form, &r)| surprisal((masses[form] - r).max(0.0)) - surprisal(masses[form]))
+                .sum();
+            p * ((bits + left) / (1.0 - total) + (1.0 - total).log2())
+        })
+        .sum()
+}
+
+/// Back candidates indexed by text, rendered form and character, to find
+/// the ones too similar to a front entry without comparing them all
+pub(crate) struct Rejections<'a> {
+    back: &'a [(&'a Entry, f64)],
+    by_form: HashMap<&'a str, Vec<usize>>,
+    by_text: HashMap<&'a str, Vec<usize>>,
+    by_char: HashMap<char, Vec<usize>>,
+    lengths: Vec<usize>,
+    repeats: Vec<bool>,
+    /// Distinct characters each back entry shares with the front entry
+    shared: Vec<usize>,
+}
+
+impl<'a> Rejections<'a> {
+    /// Index `back` whose entries render to `forms`
+    pub(crate) fn new(back: &'a [(&'a Entry, f64)], forms: &'a [String]) -> Self {
+        let mut by_form: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut by_text: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut by_char: HashMap<char, Vec<usize>> = HashMap::new();
+        for (i, (&(entry, _), form)) in back.iter().zip(forms).enumerate() {
+            by_form.entry(form).or_default().push(i);
+            by_text.entry(&entry.text).or_default().push(i);
+            for ch in distinct_chars(&entry.text) {
+                by_char.entry(ch).or_default().push(i);
+            }
+        }
+        Rejections {
+            back,
+            by_form,
+            by_text,
+            by_char,
+            lengths: back.iter().map(|(entry, _)| entry.char_count()).collect(),
+            repeats: back.iter().map(|(entry, _)| has_repeats(&entry.text)).collect(),
+            shared: vec![0; back.len()],
+        }
+    }
+    
+    /// Indices of the back entries too similar to `front`, which renders to
+    /// `form`: the same text or form, or most characters shared
+    pub(crate) fn of(&mut self, front: &Entry, form: &str) -> Vec<usize> {
+        let mut touched = Vec::new();
+        for ch in distinct_chars(&front.text) {
+            for &j in self.by_char.get(&ch).into_iter().flatten() {
+                if self.shared[j] == 0 {
+                    touched.push(j);
+                }
+                self.shared[j] += 1;
+            }
+        }
+        let length = front.char_count();
+        let repeats = has_repeats(&front.text);
+        let mut rejected: Vec<usize> = touched
+            .into_iter()
+            .filter(|&j| {
+                // Only repeated characters can add to the distinct ones
+                let count = std::mem::take(&mut self.shared[j]);
+                count * 2 > length.min(self.lengths[j])
+                    || ((repeats || self.repeats[j]) && shares_most_characters(&front.text, &self.back[j].0.text))
+            })
+            .collect();
+        rejected.extend(self.by_form.get(form).into_iter().flatten());
+        rejected.extend(self.by_text.get(front.text.as_str()).into_iter().flatten());
+        rejected.sort_unstable();
+        rejected.dedup();
+        rejected
+    }
+}
+
+/// Whether `a` and `b` share more than half of the characters of the
+/// shorter one, counting repeated characters as often as both have them
+pub fn shares_most_characters(a: &str, b: &str) -> bool {
+    let mut remaining: Vec<char> = b.chars().collect();
+    let shared = a.chars()
+        .filter(|ch| match remaining.iter().position(|other| other == ch) {
+            Some(i) => {
+                remaining.swap_remove(i);
+                true
+            }
+            None => false,
+        })
+        .count();
+    shared * 2 > a.chars().count().min(b.chars().count())
+}
+
+fn has_repeats(text: &str) -> bool {
+    distinct_chars(text).count() < text.chars().count()
+}
+
+fn distinct_chars(text: &str) -> impl Iterator<Item = char> + '_ {
+    text.char_indices().filter(move |&(i, ch)| !text[..i].contains(ch)).map(|(_, ch)| ch)
+}
+
+/// Probability of every rendered form of the candidates
+fn rendered(candidates: &[(&Entry, f64)], mode: &Mode) -> HashMap<String, f64> {
+    let mut probabilities: HashMap<String, f64> = HashMap::new();
+    for &(entry, p) in candidates {
+        *probabilities.entry(transform_entry(entry, mode)).or_default() += p;
+    }
+    probabilities
+}
+
+fn shannon(probabilities: &HashMap<String, f64>) -> f64 {
+    probabilities
+        .values()
+        .map(|&p| surprisal(p))
+        .sum()
+}
+
+/// The share `-p log2 p` of an outcome of probability `p` in the entropy
+fn surprisal(p: f64) -> f64 {
+    if p > 0.0 { -p * p.log2() } else { 0.0 }
+}
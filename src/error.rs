@@ -0,0 +1,81 @@
+use crate::generator::BatchError;
+use std::fmt;
+
+/// Why a generator cannot produce passwords as configured
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The corpora a part is drawn from have no entries, e.g. an empty
+    /// custom corpus; `corpora` are their names joined with "/"
+    EmptyCorpus { corpora: String },
+    /// None of the entries is within the length and tier limits
+    NoCandidates { corpora: String },
+    /// No back part is different enough from the front part `front`
+    NoDistinctBack { front: String, corpora: String },
+    /// A segment length range whose minimum exceeds its maximum
+    InvalidLength { min: usize, max: usize },
+    /// Tier or source weights that are negative, not finite, or all zero
+    InvalidWeights,
+    /// A separator containing letters, digits, whitespace or control
+    /// characters, which would blur where segments end
+    InvalidSeparator(String),
+    /// A corpus of the generator has no entry `index`, or there is no
+    /// corpus of that name
+    UnknownEntry { corpus: String, index: usize },
+    /// A backup code that is mistyped or not a backup code at all
+    InvalidBackup,
+    /// A backup code made with other corpora, modes or separator than
+    /// those of the generator decoding it
+    BackupMismatch,
+    /// The estimated entropy is below the required minimum
+    InsufficientEntropy { bits: f64, required: f64 },
+    /// A batch could not be generated
+    Batch(BatchError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::EmptyCorpus { corpora } => write!(f, "No {} data available", corpora),
+            Error::NoCandidates { corpora } => write!(f, "No {} data within the length and tier limits", corpora),
+            Error::NoDistinctBack { front, corpora } if front.is_empty() => {
+                write!(f, "No {} data distinct from the front part", corpora)
+            }
+            Error::NoDistinctBack { front, corpora } => {
+                write!(f, "No {} data distinct from the front part \"{}\"", corpora, front)
+            }
+            Error::InvalidLength { min, max } => {
+                write!(f, "segment length minimum {} exceeds the maximum {}", min, max)
+            }
+            Error::InvalidWeights => write!(f, "weights must be finite, non-negative and not all zero"),
+            Error::InvalidSeparator(separator) => write!(
+                f,
+                "separator {:?} contains letters, digits, whitespace or control characters",
+                separator
+            ),
+            Error::UnknownEntry { corpus, index } => write!(f, "No entry {} in {} data", index, corpus),
+            Error::InvalidBackup => write!(f, "invalid backup code: mistyped or checksum mismatch"),
+            Error::BackupMismatch => write!(f, "backup code was made with other corpora, modes or separator"),
+            Error::InsufficientEntropy { bits, required } => write!(
+                f,
+                "estimated entropy of {:.1} bits is below the required {:.1} bits",
+                bits, required
+            ),
+            Error::Batch(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Batch(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<BatchError> for Error {
+    fn from(error: BatchError) -> Self {
+        Error::Batch(error)
+    }
+}
@@ -1,13 +1,93 @@
-use crate::provider::{Provider, Source};
-use crate::transform::{transform_with_mode, Mode, DualMode, TransformMode};
+mod backup;
+#[cfg(feature = "serde")]
+mod recipe;
+
+use crate::corpus::{Corpus, Entry, Tier};
+use crate::error::Error;
+use crate::entropy::{distinct_back_bits, segment_bits, shares_most_characters, Rejections, NUMBER_BITS};
+use crate::passphrase::{Passphrase, Segment};
+use crate::provider::{locate, no_candidates, Part, Provider, Sampling, Source};
+use crate::transform::{transform_entries, transform_entry, Mode, DualMode, TransformMode};
+use rand::seq::IndexedRandom;
 use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::Range;
+use std::sync::{Arc, OnceLock};
+
+/// How many times in a row a batch may draw a password it already has
+/// before giving up
+const MAX_BATCH_RETRIES: usize = 1000;
+
+pub use backup::BACKUP_VERSION;
+#[cfg(feature = "serde")]
+pub use recipe::RECIPE_VERSION;
+
+/// Options for [`Generator::generate_batch`]
+#[derive(Clone, Debug, Default)]
+pub struct BatchOptions {
+    /// Use every source line in at most one password of the batch
+    pub distinct_lines: bool,
+}
+
+/// Why a batch of passwords could not be generated
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BatchError {
+    /// More passwords were requested than the settings can produce
+    TooMany { requested: usize, available: usize },
+    /// Draws kept repeating passwords or lines already in the batch; only
+    /// `generated` passwords were found
+    Exhausted { requested: usize, generated: usize },
+}
 
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchError::TooMany { requested, available } => write!(
+                f,
+                "{} passwords requested but the settings allow at most {}",
+                requested, available
+            ),
+            BatchError::Exhausted { requested, generated } => write!(
+                f,
+                "only {} of {} unique passwords could be generated",
+                generated, requested
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+#[derive(Clone)]
 pub struct Generator {
     source: Source,
     mode: TransformMode,
     separator: String,
     add_number: bool,
     random_capitalize: bool,
+    meaning_hint: bool,
+    sampling: Sampling,
+    distinct: bool,
+    min_entropy: Option<f64>,
+    cache: Cache,
+}
+
+/// Work shared by a generator and its clones until a setting changes: the
+/// provider with its prepared candidates and the entropy estimate
+#[derive(Clone, Default)]
+struct Cache(Arc<CacheCell>);
+
+#[derive(Default)]
+struct CacheCell {
+    provider: OnceLock<Provider>,
+    entropy_bits: OnceLock<f64>,
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Generator {
@@ -19,31 +99,42 @@ impl Generator {
             separator: "-".to_string(),
             add_number: true,
             random_capitalize: false,
+            meaning_hint: false,
+            sampling: Sampling::default(),
+            distinct: true,
+            min_entropy: None,
+            cache: Cache::default(),
         }
     }
     
     /// Use initials (首字母)
     pub fn initials(mut self) -> Self {
         self.mode = TransformMode::Single(Mode::PinyinInit);
-        self
+        self.changed()
     }
 
     /// Use full Pinyin
     pub fn full_pinyin(mut self) -> Self {
         self.mode = TransformMode::Single(Mode::PinyinFull);
-        self
+        self.changed()
     }
 
     /// Use original Chinese (no transformation)
     pub fn chinese(mut self) -> Self {
         self.mode = TransformMode::Single(Mode::Chinese);
-        self
+        self.changed()
+    }
+
+    /// Use Zhuyin (注音符号) with tone marks
+    pub fn zhuyin(mut self) -> Self {
+        self.mode = TransformMode::Single(Mode::Zhuyin);
+        self.changed()
     }
     
     /// Set dual mode with different modes for front and back parts
     pub fn dual_mode(mut self, front: Mode, back: Mode) -> Self {
         self.mode = TransformMode::Dual(DualMode { front, back });
-        self
+        self.changed()
     }
     
     /// Convenience method: front full pinyin, back initials
@@ -52,7 +143,7 @@ impl Generator {
             front: Mode::PinyinFull, 
             back: Mode::PinyinInit 
         });
-        self
+        self.changed()
     }
     
     /// Convenience method: front initials, back full pinyin
@@ -61,55 +152,196 @@ impl Generator {
             front: Mode::PinyinInit, 
             back: Mode::PinyinFull 
         });
-        self
+        self.changed()
     }
     
     /// Use poetry source only
     pub fn poetry_only(mut self) -> Self {
         self.source = Source::Poetry;
-        self
+        self.changed()
     }
     
     /// Use words source only
     pub fn words_only(mut self) -> Self {
         self.source = Source::Words;
-        self
+        self.changed()
     }
     
     /// Use words-poetry combination (front: words, back: poetry)
     pub fn words_poetry(mut self) -> Self {
         self.source = Source::WordsPoetry;
-        self
+        self.changed()
     }
     
     /// Use poetry-words combination (front: poetry, back: words)
     pub fn poetry_words(mut self) -> Self {
         self.source = Source::PoetryWords;
-        self
+        self.changed()
     }
     
     /// Use words-words combination (front: words, back: words)
     pub fn words_words(mut self) -> Self {
         self.source = Source::WordsWords;
-        self
+        self.changed()
     }
     
     /// Use poetry-poetry combination (front: poetry, back: poetry)
     pub fn poetry_poetry(mut self) -> Self {
         self.source = Source::PoetryPoetry;
+        self.changed()
+    }
+    
+    /// Use idioms (成语) for both parts
+    pub fn chengyu_only(mut self) -> Self {
+        self.source = Source::Chengyu;
+        self.changed()
+    }
+    
+    /// Use 宋词 lines for both parts
+    pub fn song_ci_only(mut self) -> Self {
+        self.source = Source::SongCi;
+        self.changed()
+    }
+    
+    /// Use 元曲 lines for both parts
+    pub fn yuan_qu_only(mut self) -> Self {
+        self.source = Source::YuanQu;
+        self.changed()
+    }
+    
+    /// Use 论语 quotations for both parts
+    pub fn lun_yu_only(mut self) -> Self {
+        self.source = Source::LunYu;
+        self.changed()
+    }
+    
+    /// Use 道德经 quotations for both parts
+    pub fn dao_de_jing_only(mut self) -> Self {
+        self.source = Source::DaoDeJing;
+        self.changed()
+    }
+    
+    /// Use 诗经 lines for both parts
+    pub fn shi_jing_only(mut self) -> Self {
+        self.source = Source::ShiJing;
+        self.changed()
+    }
+    
+    /// Use classical prose quotations (古文) for both parts
+    pub fn gu_wen_only(mut self) -> Self {
+        self.source = Source::GuWen;
+        self.changed()
+    }
+    
+    /// Only use lines of `min` to `max` characters for each part, e.g.
+    /// `segment_length(4, 7)` to skip the shortest and longest lines of 宋词
+    pub fn segment_length(mut self, min: usize, max: usize) -> Self {
+        self.sampling.length = Some(min..=max);
+        self.changed()
+    }
+    
+    /// Only use lines of `tier` or better known, e.g. `max_tier(Tier::Famous)`
+    /// for lines learned at school. Fewer lines lower `entropy_bits()`.
+    pub fn max_tier(self, tier: Tier) -> Self {
+        let weights = Tier::ALL.map(|t| if t <= tier { 1.0 } else { 0.0 });
+        self.tier_weights(weights[0], weights[1], weights[2])
+    }
+    
+    /// Pick lines of each tier in proportion to these weights within a
+    /// corpus, e.g. `tier_weights(4.0, 2.0, 1.0)` to favour famous lines
+    /// without leaving any out. Entries without a tier count as `Common`.
+    pub fn tier_weights(mut self, famous: f64, common: f64, obscure: f64) -> Self {
+        self.sampling.tier_weights = Some([famous, common, obscure]);
+        self.changed()
+    }
+    
+    /// Pick every line with the same probability again
+    pub fn all_tiers(mut self) -> Self {
+        self.sampling.tier_weights = None;
+        self.changed()
+    }
+    
+    /// Show the meaning of idioms in the source hint, e.g.
+    /// "一帆风顺（船挂满帆顺风行驶，比喻非常顺利）"
+    pub fn meaning_hint(mut self) -> Self {
+        self.meaning_hint = true;
         self
     }
     
+    /// Show idioms without their meaning again (default)
+    pub fn no_meaning_hint(mut self) -> Self {
+        self.meaning_hint = false;
+        self
+    }
+    
+    /// Use a custom corpus for both parts
+    pub fn corpus(mut self, corpus: Corpus) -> Self {
+        self.source = Source::Custom(corpus);
+        self.changed()
+    }
+
+    /// Draw both parts uniformly from every entry of the sources' corpora,
+    /// e.g. `union(vec![Source::Poetry, Source::Words])` makes each of the
+    /// poetry lines and words equally likely
+    pub fn union(mut self, sources: Vec<Source>) -> Self {
+        self.source = Source::Union(sources);
+        self.changed()
+    }
+    
+    /// Pick the source of each part with these relative weights, then an
+    /// entry of that source, e.g. `weighted(vec![(Source::Poetry, 3.0),
+    /// (Source::Chengyu, 1.0)])` takes poetry for 3 in 4 parts
+    pub fn weighted(mut self, sources: Vec<(Source, f64)>) -> Self {
+        self.source = Source::Weighted(sources);
+        self.changed()
+    }
+    
+    /// Use different sources for front and back parts, e.g. a custom corpus
+    /// in front and built-in poetry at the back
+    pub fn sources(mut self, front: Source, back: Source) -> Self {
+        self.source = Source::Pair(Box::new(front), Box::new(back));
+        self.changed()
+    }
+
+    /// Allow the back part to repeat the front part
+    ///
+    /// By default the parts differ in text and in rendered form, and share
+    /// at most half of the characters of the shorter one.
+    pub fn allow_repeats(mut self) -> Self {
+        self.distinct = false;
+        self.changed()
+    }
+    
+    /// Keep the parts distinct again (default); see
+    /// [`allow_repeats`](Self::allow_repeats)
+    pub fn forbid_repeats(mut self) -> Self {
+        self.distinct = true;
+        self.changed()
+    }
+    
     /// Set separator between parts
     pub fn separator(mut self, sep: &str) -> Self {
         self.separator = sep.to_string();
         self
     }
     
+    /// Require at least `bits` of estimated entropy; checked by
+    /// [`build`](Self::build)
+    pub fn min_entropy(mut self, bits: f64) -> Self {
+        self.min_entropy = Some(bits);
+        self
+    }
+    
     /// Don't add a random number at the end
     pub fn no_number(mut self) -> Self {
         self.add_number = false;
-        self
+        self.changed()
+    }
+    
+    /// Add a random number at the end again (default)
+    pub fn with_number(mut self) -> Self {
+        self.add_number = true;
+        self.changed()
     }
     
     /// Enable random capitalization of at least one letter
@@ -118,126 +350,537 @@ impl Generator {
         self
     }
     
-    /// Apply random capitalization to at least one letter in the password
-    fn apply_random_capitalization(password: &str) -> String {
-        let mut chars: Vec<char> = password.chars().collect();
-        let mut rng = rand::rng();
-        
-        // Find all letter positions
-        let letter_positions: Vec<usize> = chars
-            .iter()
-            .enumerate()
-            .filter_map(|(i, &c)| if c.is_ascii_alphabetic() { Some(i) } else { None })
-            .collect();
-        
-        if letter_positions.is_empty() {
-            return password.to_string();
+    /// Leave every letter lower case again (default)
+    pub fn no_random_capitalize(mut self) -> Self {
+        self.random_capitalize = false;
+        self
+    }
+    
+    /// Pick positions of 1 to 3 letters of `password` to capitalize, none
+    /// if it has no letters
+    fn random_capitals(password: &str) -> Vec<usize> {
+        let letters = letter_positions(password, 0..usize::MAX);
+        if letters.is_empty() {
+            return Vec::new();
         }
         
         // Randomly choose 1-3 letters to capitalize (but at least 1)
-        let num_to_capitalize = rng.random_range(1..=3.min(letter_positions.len()));
-        
-        // Randomly select positions to capitalize
-        let mut selected_positions = Vec::new();
-        for _ in 0..num_to_capitalize {
-            loop {
-                let pos = letter_positions[rng.random_range(0..letter_positions.len())];
-                if !selected_positions.contains(&pos) {
-                    selected_positions.push(pos);
-                    break;
-                }
-            }
+        let count = rand::rng().random_range(1..=3.min(letters.len()));
+        pick_capitals(&letters, count)
+    }
+    
+    /// Drop what was worked out for the old settings
+    fn changed(mut self) -> Self {
+        self.cache = Cache::default();
+        self
+    }
+    
+    fn provider(&self) -> &Provider {
+        self.cache.0.provider.get_or_init(|| Provider::with_sampling(self.source.clone(), self.sampling.clone()))
+    }
+    
+    /// Modes the front and back parts are rendered in
+    fn modes(&self) -> (&Mode, &Mode) {
+        match &self.mode {
+            TransformMode::Single(mode) => (mode, mode),
+            TransformMode::Dual(dual) => (&dual.front, &dual.back),
         }
-        
-        // Apply capitalization
-        for &pos in &selected_positions {
-            chars[pos] = chars[pos].to_ascii_uppercase();
+    }
+    
+    /// Whether two parts are too alike for one password: the same text, the
+    /// same rendered form (e.g. "yw-yw"), or sharing more than half of the
+    /// characters of the shorter part
+    fn too_similar(&self, front: &Entry, back: &Entry) -> bool {
+        if front.text == back.text {
+            return true;
         }
-        
-        chars.into_iter().collect()
+        let (front_mode, back_mode) = self.modes();
+        if transform_entry(front, front_mode) == transform_entry(back, back_mode) {
+            return true;
+        }
+        shares_most_characters(&front.text, &back.text)
     }
     
-    /// Generate a random passphrase
-    pub fn generate(&self) -> String {
-        let provider = Provider::new(self.source.clone());
-        let mut rng = rand::rng();
-        
-        let parts = match self.source {
-            Source::Mixed => {
-                vec![
-                    provider.get(),
-                    provider.get_poetry(),
-                ]
+    /// Pick the front and back parts from the provider
+    fn parts<'a>(&self, provider: &'a Provider) -> Vec<&'a Entry> {
+        self.try_parts(provider).unwrap_or_else(|e| panic!("{}", e))
+    }
+    
+    fn try_parts<'a>(&self, provider: &'a Provider) -> Result<Vec<&'a Entry>, Error> {
+        let front = provider.try_front_entry()?;
+        let back = if self.distinct {
+            provider.try_back_entry_where(&front.text, |back| !self.too_similar(front, back))?
+        } else {
+            provider.try_back_entry()?
+        };
+        Ok(vec![front, back])
+    }
+    
+    /// Settings that are wrong whatever the corpora hold
+    fn check_settings(&self) -> Result<(), Error> {
+        let blurs = |ch: char| ch.is_alphanumeric() || ch.is_whitespace() || ch.is_control();
+        if self.separator.chars().any(blurs) {
+            return Err(Error::InvalidSeparator(self.separator.clone()));
+        }
+        if let Some(length) = &self.sampling.length {
+            if length.start() > length.end() {
+                return Err(Error::InvalidLength { min: *length.start(), max: *length.end() });
             }
-            Source::WordsPoetry | Source::PoetryWords | 
-            Source::WordsWords | Source::PoetryPoetry => {
-                vec![
-                    provider.get_front(),
-                    provider.get_back(),
-                ]
+        }
+        let valid_tiers = self.sampling.tier_weights.is_none_or(|weights| {
+            weights.iter().all(|w| w.is_finite() && *w >= 0.0) && weights.iter().any(|&w| w > 0.0)
+        });
+        if !valid_tiers || !self.source.has_valid_weights() {
+            return Err(Error::InvalidWeights);
+        }
+        Ok(())
+    }
+    
+    /// Check the settings against the corpora, so that
+    /// [`generate`](Self::generate) cannot panic
+    ///
+    /// Reports an invalid separator, length range or weights, a part with
+    /// nothing to draw from, a front part that no back part is distinct
+    /// from, and entropy below [`min_entropy`](Self::min_entropy).
+    pub fn build(self) -> Result<Self, Error> {
+        self.check_settings()?;
+        let front = self.provider().candidates(Part::Front);
+        let back = self.provider().candidates(Part::Back);
+        if front.is_empty() {
+            return Err(no_candidates(&self.source.front_corpora(), &self.sampling));
+        }
+        if back.is_empty() {
+            return Err(no_candidates(&self.source.back_corpora(), &self.sampling));
+        }
+        if self.distinct {
+            let stuck = front.iter()
+                .find(|(front_entry, _)| back.iter().all(|(back_entry, _)| self.too_similar(front_entry, back_entry)));
+            if let Some((front_entry, _)) = stuck {
+                let names: Vec<&str> = self.source.back_corpora().iter().map(|(corpus, _)| corpus.name()).collect();
+                return Err(Error::NoDistinctBack { front: front_entry.text.clone(), corpora: names.join("/") });
             }
-            _ => {
-                vec![
-                    provider.get(),
-                    provider.get(),
-                ]
+        }
+        if let Some(required) = self.min_entropy {
+            let bits = self.entropy_bits();
+            if bits < required {
+                return Err(Error::InsufficientEntropy { bits, required });
             }
-        };
+        }
+        Ok(self)
+    }
+    
+    /// Transform the parts and add the number and capitalization
+    fn render(&self, parts: &[&Entry]) -> String {
+        self.render_passphrase(parts, 0.0).password
+    }
+    
+    /// Render the parts into a password, keeping each segment and the
+    /// number alongside it
+    fn render_passphrase(&self, parts: &[&Entry], entropy_bits: f64) -> Passphrase {
+        let passphrase = self.assemble(parts, self.origins(parts), self.draw_number(), entropy_bits);
+        self.capitalize_randomly(passphrase)
+    }
+    
+    /// The corpus name and index of each drawn part
+    fn origins(&self, parts: &[&Entry]) -> Vec<(String, usize)> {
+        let front = self.source.front_corpora();
+        let back = self.source.back_corpora();
+        parts.iter().enumerate()
+            .map(|(i, entry)| locate(if i == 0 { &front } else { &back }, entry).unwrap_or_default())
+            .collect()
+    }
+    
+    /// Capitalize random letters of an uncapitalized passphrase, if the
+    /// settings say so
+    fn capitalize_randomly(&self, passphrase: Passphrase) -> Passphrase {
+        if self.random_capitalize {
+            let capitals = Self::random_capitals(&passphrase.password);
+            with_capitals(passphrase, capitals)
+        } else {
+            passphrase
+        }
+    }
+    
+    /// A random number to append, if the settings add one
+    fn draw_number(&self) -> Option<u32> {
+        self.add_number.then(|| rand::rng().random_range(1000..10000))  // 0.9: gen_range -> random_range, 4位数字
+    }
+    
+    /// Render the parts with a given number, without capitalization
+    fn assemble(
+        &self,
+        parts: &[&Entry],
+        origins: Vec<(String, usize)>,
+        number: Option<u32>,
+        entropy_bits: f64,
+    ) -> Passphrase {
+        let (front_mode, back_mode) = self.modes();
         
-        let transformed = transform_with_mode(&parts, &self.mode);
+        let transformed = transform_entries(parts, &self.mode);
         
         let mut result = transformed.join(&self.separator);
         
-        if self.add_number {
-            let num: u32 = rng.random_range(1000..10000);  // 0.9: gen_range -> random_range, 4位数字
+        if let Some(num) = number {
             result.push_str(&format!("{}{}", &self.separator, num));
         }
         
-        if self.random_capitalize {
-            result = Self::apply_random_capitalization(&result);
+        let segments = parts.iter().zip(transformed).zip(origins).enumerate()
+            .map(|(i, ((entry, text), (corpus, index)))| Segment {
+                entry: (*entry).clone(),
+                mode: if i == 0 { front_mode.clone() } else { back_mode.clone() },
+                text,
+                corpus,
+                index,
+            })
+            .collect();
+        
+        Passphrase {
+            segments,
+            number,
+            separator: self.separator.clone(),
+            password: result,
+            capitals: Vec::new(),
+            entropy_bits,
         }
+    }
+    
+    /// Generate a random passphrase; see
+    /// [`generate_passphrase`](Self::generate_passphrase) for its parts
+    ///
+    /// # Panics
+    ///
+    /// If a part has nothing to draw from, e.g. an empty custom corpus; see
+    /// [`try_generate`](Self::try_generate) and [`build`](Self::build).
+    pub fn generate(&self) -> String {
+        let provider = self.provider();
         
-        result
+        self.render(&self.parts(provider))
     }
     
-    pub fn generate_multiple(&self, count: usize) -> Vec<String> {
-        (0..count).map(|_| self.generate()).collect()
+    /// Generate a random passphrase, or report why none can be generated
+    ///
+    /// Checks the settings and the draws but not the entropy, which
+    /// [`build`](Self::build) checks once.
+    pub fn try_generate(&self) -> Result<String, Error> {
+        self.check_settings()?;
+        let provider = self.provider();
+        
+        Ok(self.render(&self.try_parts(provider)?))
     }
     
-    pub fn generate_with_source(&self) -> (String, String) {
-        let provider = Provider::new(self.source.clone());
+    /// Generate a random passphrase together with its segments, number and
+    /// estimated entropy
+    pub fn generate_passphrase(&self) -> Passphrase {
+        let provider = self.provider();
         
-        let parts = match self.source {
-            Source::Mixed => {
-                vec![provider.get(), provider.get_poetry()]
-            }
-            Source::WordsPoetry | Source::PoetryWords | 
-            Source::WordsWords | Source::PoetryPoetry => {
-                vec![
-                    provider.get_front(),
-                    provider.get_back(),
-                ]
-            }
-            _ => {
-                vec![provider.get(), provider.get()]
+        self.render_passphrase(&self.parts(provider), self.entropy_bits())
+    }
+    
+    /// Draw segment `index` of `passphrase` again, keeping the other
+    /// segments, the number and their capitalization
+    ///
+    /// The new entry differs from the old one when the corpus allows, and
+    /// from the other segment unless [`allow_repeats`](Self::allow_repeats)
+    /// is set. It gets as many capitals as the old one had.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is not a segment of `passphrase`.
+    pub fn reroll_segment(&self, passphrase: &Passphrase, index: usize) -> Result<Passphrase, Error> {
+        let mut parts: Vec<&Entry> = passphrase.segments.iter().map(|segment| &segment.entry).collect();
+        let old = parts[index];
+        let (part, corpora) = if index == 0 {
+            (Part::Front, self.source.front_corpora())
+        } else {
+            (Part::Back, self.source.back_corpora())
+        };
+        let other = if index == 0 { parts.get(1) } else { parts.first() };
+        let other = other.map_or("", |entry| entry.text.as_str());
+        
+        let fits = |entry: &Entry| !self.distinct || match index {
+            0 => parts.get(1).is_none_or(|back| !self.too_similar(entry, back)),
+            _ => !self.too_similar(parts[0], entry),
+        };
+        let provider = self.provider();
+        let new = match provider.pick_where(part, other, |entry| entry.text != old.text && fits(entry)) {
+            Err(Error::NoDistinctBack { .. }) => provider.pick_where(part, other, fits)?,
+            result => result?,
+        };
+        parts[index] = new;
+        let mut origins = passphrase.origins();
+        origins[index] = locate(&corpora, new).unwrap_or_default();
+        
+        let rerolled = self.assemble(&parts, origins, passphrase.number, passphrase.entropy_bits);
+        let old_range = passphrase.segment_range(index);
+        let new_range = rerolled.segment_range(index);
+        let mut capitals = Vec::new();
+        let mut dropped = 0;
+        for &position in &passphrase.capitals {
+            if position < old_range.start {
+                capitals.push(position);
+            } else if position < old_range.end {
+                dropped += 1;
+            } else {
+                capitals.push(position - old_range.end + new_range.end);
             }
+        }
+        capitals.extend(pick_capitals(&letter_positions(&rerolled.password, new_range), dropped));
+        if capitals.is_empty() && dropped > 0 {
+            // The new segment has no letters, e.g. in Chinese mode
+            return Ok(self.capitalize_randomly(rerolled));
+        }
+        capitals.sort_unstable();
+        
+        Ok(with_capitals(rerolled, capitals))
+    }
+    
+    /// Draw a new number for `passphrase`, keeping the segments and their
+    /// capitalization; the number is dropped if the settings add none
+    pub fn reroll_number(&self, passphrase: &Passphrase) -> Passphrase {
+        let rerolled = Passphrase { number: self.draw_number(), ..passphrase.clone() };
+        
+        with_capitals(rerolled, passphrase.capitals.clone())
+    }
+    
+    /// Capitalize other random letters of `passphrase`, or none if random
+    /// capitalization is off, keeping the segments and the number
+    pub fn reroll_capitalization(&self, passphrase: &Passphrase) -> Passphrase {
+        let plain = with_capitals(passphrase.clone(), Vec::new());
+        
+        self.capitalize_randomly(plain)
+    }
+    
+    /// Render the entries of `passphrase` with these settings, e.g. after
+    /// switching to another mode or separator
+    ///
+    /// The entries and, if the settings add one, the number are kept.
+    /// Random capitalization is applied afresh, as the letters change.
+    pub fn rerender(&self, passphrase: &Passphrase) -> Passphrase {
+        let parts: Vec<&Entry> = passphrase.segments.iter().map(|segment| &segment.entry).collect();
+        let number = if self.add_number { passphrase.number.or_else(|| self.draw_number()) } else { None };
+        
+        self.capitalize_randomly(self.assemble(&parts, passphrase.origins(), number, self.entropy_bits()))
+    }
+    
+    /// Rebuild a passphrase from the corpus name and index of each segment,
+    /// as recorded in [`Segment::corpus`](crate::Segment::corpus) and
+    /// [`Segment::index`](crate::Segment::index), without drawing anything
+    ///
+    /// Returns [`Error::UnknownEntry`] if a corpus of the part has no such
+    /// entry. The result is not capitalized.
+    pub fn restore(&self, origins: &[(&str, usize)], number: Option<u32>) -> Result<Passphrase, Error> {
+        let front = self.source.front_corpora();
+        let back = self.source.back_corpora();
+        let parts = origins.iter().enumerate()
+            .map(|(i, &(name, index))| {
+                let corpora = if i == 0 { &front } else { &back };
+                corpora.iter()
+                    .filter(|(corpus, _)| corpus.name() == name)
+                    .find_map(|(corpus, _)| corpus.entry(index))
+                    .ok_or_else(|| Error::UnknownEntry { corpus: name.to_string(), index })
+            })
+            .collect::<Result<Vec<&Entry>, Error>>()?;
+        let origins = origins.iter().map(|&(name, index)| (name.to_string(), index)).collect();
+        
+        Ok(self.assemble(&parts, origins, number, self.entropy_bits()))
+    }
+    
+    /// Estimated entropy of generated passphrases in bits
+    ///
+    /// Counts the choice of each segment in its rendered form and the number.
+    /// Length and tier limits, tier weights and back parts drawn again for
+    /// being too similar to the front part are taken into account; see
+    /// [`distinct_back_bits`]. This errs low: random capitalization is not
+    /// counted, nor which of several front parts that render the same was
+    /// drawn.
+    pub fn entropy_bits(&self) -> f64 {
+        *self.cache.0.entropy_bits.get_or_init(|| self.count_entropy_bits())
+    }
+    
+    fn count_entropy_bits(&self) -> f64 {
+        let (front_mode, back_mode) = self.modes();
+        let front = self.provider().candidates(Part::Front);
+        let back = self.provider().candidates(Part::Back);
+        
+        let mut bits = segment_bits(&front, front_mode);
+        bits += if self.distinct {
+            distinct_back_bits(&front, front_mode, &back, back_mode)
+        } else {
+            segment_bits(&back, back_mode)
         };
+        if self.add_number {
+            bits += NUMBER_BITS;
+        }
+        bits
+    }
+    
+    /// Generate `count` passwords, which may repeat; see
+    /// [`generate_unique`](Self::generate_unique)
+    pub fn generate_multiple(&self, count: usize) -> Vec<String> {
+        (0..count).map(|_| self.generate()).collect()
+    }
+    
+    /// Generate `count` different passwords
+    pub fn generate_unique(&self, count: usize) -> Result<Vec<String>, BatchError> {
+        let batch = self.generate_batch(count, &BatchOptions::default())?;
+        Ok(batch.into_iter().map(|passphrase| passphrase.password).collect())
+    }
+    
+    /// Upper bound on the number of passwords a batch can hold
+    ///
+    /// Counts the pairs of rendered front and back parts that can follow
+    /// each other, times the 9000 numbers; passwords that differ only in
+    /// capitalization count once. With `distinct_lines`, each password uses
+    /// up two lines.
+    pub fn batch_capacity(&self, options: &BatchOptions) -> usize {
+        let (front_mode, back_mode) = self.modes();
+        let front = self.provider().candidates(Part::Front);
+        let back = self.provider().candidates(Part::Back);
         
-        let source = parts.join(&self.separator);
-        let transformed = transform_with_mode(&parts, &self.mode);
+        if options.distinct_lines {
+            let front_lines: HashSet<&str> = front.iter().map(|(entry, _)| entry.text.as_str()).collect();
+            let back_lines: HashSet<&str> = back.iter().map(|(entry, _)| entry.text.as_str()).collect();
+            let all_lines = front_lines.union(&back_lines).count();
+            return front_lines.len().min(back_lines.len()).min(all_lines / 2);
+        }
         
-        let mut password = transformed.join(&self.separator);
+        let forms: Vec<String> = back.iter().map(|(entry, _)| transform_entry(entry, back_mode)).collect();
+        let mut form_sizes: HashMap<&str, usize> = HashMap::new();
+        for form in &forms {
+            *form_sizes.entry(form).or_default() += 1;
+        }
         
+        // Back forms that no entry of the front form can be followed by
+        let mut blocked: HashMap<String, HashSet<&str>> = HashMap::new();
+        let mut rejections = Rejections::new(&back, &forms);
+        for &(entry, _) in &front {
+            let front_form = transform_entry(entry, front_mode);
+            let mut full = HashSet::new();
+            if self.distinct {
+                let mut rejected: HashMap<&str, usize> = HashMap::new();
+                for j in rejections.of(entry, &front_form) {
+                    *rejected.entry(&forms[j]).or_default() += 1;
+                }
+                full.extend(rejected.into_iter().filter(|&(form, n)| n == form_sizes[form]).map(|(form, _)| form));
+            }
+            blocked.entry(front_form)
+                .and_modify(|blocked| blocked.retain(|form| full.contains(form)))
+                .or_insert(full);
+        }
+        let mut pairs: usize = blocked.values().map(|blocked| form_sizes.len() - blocked.len()).sum();
         if self.add_number {
-            let mut rng = rand::rng();
-            let num: u32 = rng.random_range(1000..10000);
-            password.push_str(&format!("{}{}", &self.separator, num));
+            pairs = pairs.saturating_mul(9000);
+        }
+        pairs
+    }
+    
+    /// Generate `count` different passphrases with their segments
+    ///
+    /// Returns [`BatchError::TooMany`] without generating anything when
+    /// `count` exceeds [`batch_capacity`](Self::batch_capacity). The
+    /// candidates are prepared once, so batches of thousands are cheap.
+    pub fn generate_batch(
+        &self,
+        count: usize,
+        options: &BatchOptions,
+    ) -> Result<Vec<Passphrase>, BatchError> {
+        let available = self.batch_capacity(options);
+        if count > available {
+            return Err(BatchError::TooMany { requested: count, available });
         }
         
-        if self.random_capitalize {
-            password = Self::apply_random_capitalization(&password);
+        let entropy_bits = self.entropy_bits();
+        let provider = self.provider();
+        let mut passwords = HashSet::with_capacity(count);
+        let mut used_lines: HashSet<&str> = HashSet::new();
+        let mut batch = Vec::with_capacity(count);
+        let mut retries = 0;
+        
+        while batch.len() < count {
+            let unused = |entry: &Entry| !used_lines.contains(entry.text.as_str());
+            let parts = provider.pick_where(Part::Front, "", unused).ok().and_then(|front_entry| {
+                let back_entry = provider.pick_where(Part::Back, "", |entry| {
+                    unused(entry)
+                        && !(self.distinct && self.too_similar(front_entry, entry))
+                        && !(options.distinct_lines && entry.text == front_entry.text)
+                }).ok()?;
+                Some([front_entry, back_entry])
+            });
+            
+            let new = parts.map(|parts| (self.render_passphrase(&parts, entropy_bits), parts))
+                .filter(|(passphrase, _)| !passwords.contains(&passphrase.password.to_lowercase()));
+            let Some((passphrase, parts)) = new else {
+                retries += 1;
+                if retries > MAX_BATCH_RETRIES {
+                    return Err(BatchError::Exhausted { requested: count, generated: batch.len() });
+                }
+                continue;
+            };
+            
+            retries = 0;
+            if options.distinct_lines {
+                used_lines.extend(parts.iter().map(|entry| entry.text.as_str()));
+            }
+            passwords.insert(passphrase.password.to_lowercase());
+            batch.push(passphrase);
         }
         
-        (password, source)
+        Ok(batch)
+    }
+    
+    pub fn generate_with_source(&self) -> (String, String) {
+        let (password, entries) = self.generate_with_entries();
+        
+        (password, self.source_hint(&entries))
     }
+    
+    /// Format the source text of entries as a memory hint, adding the
+    /// meaning of idioms when `meaning_hint` is enabled
+    pub fn source_hint(&self, entries: &[Entry]) -> String {
+        entries.iter()
+            .map(|entry| match &entry.meaning {
+                Some(meaning) if self.meaning_hint => format!("{}（{}）", entry.text, meaning),
+                _ => entry.text.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(&self.separator)
+    }
+    
+    /// Generate a passphrase together with the corpus entries it was built
+    /// from, including their author and title when the corpus has them
+    pub fn generate_with_entries(&self) -> (String, Vec<Entry>) {
+        let provider = self.provider();
+        
+        let parts = self.parts(provider);
+        let password = self.render(&parts);
+        
+        (password, parts.into_iter().cloned().collect())
+    }
+}
+
+/// Positions of the ASCII letters of `text` within the char range `range`
+fn letter_positions(text: &str, range: Range<usize>) -> Vec<usize> {
+    text.chars()
+        .enumerate()
+        .filter(|&(i, c)| range.contains(&i) && c.is_ascii_alphabetic())
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Up to `count` different positions of `letters`, in order
+fn pick_capitals(letters: &[usize], count: usize) -> Vec<usize> {
+    let mut capitals: Vec<usize> = letters.choose_multiple(&mut rand::rng(), count).copied().collect();
+    capitals.sort_unstable();
+    capitals
+}
+
+/// Set the capitals of `passphrase`, rendering the password again
+fn with_capitals(mut passphrase: Passphrase, capitals: Vec<usize>) -> Passphrase {
+    passphrase.password = passphrase.uncapitalized()
+        .chars()
+        .enumerate()
+        .map(|(i, c)| if capitals.contains(&i) { c.to_ascii_uppercase() } else { c })
+        .collect();
+    passphrase.capitals = capitals;
+    passphrase
 }
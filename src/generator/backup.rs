@@ -0,0 +1,320 @@
+//! Backup codes: a passphrase reduced to the indices of its entries
+//!
+//! A code holds the format version, a 32-bit hash of the settings that
+//! decide how entries are rendered, the corpus and index of each segment,
+//! the number and the capitalized positions, followed by a CRC-16 of all of
+//! it. Only a generator with the same corpora, modes and separator turns it
+//! back into the password; any other reports [`Error::BackupMismatch`].
+//!
+//! The bytes are written either as Crockford base32 in groups of four,
+//! e.g. `0G2X-A01P-...`, or as proquints, pronounceable five-letter words
+//! for 16 bits each, e.g. `lusab-babad-...`. Decoding accepts both.
+
+use super::{with_capitals, Generator};
+use crate::corpus::{Corpus, Entry};
+use crate::error::Error;
+use crate::passphrase::Passphrase;
+
+/// Version of the backup code layout written by this crate
+pub const BACKUP_VERSION: u8 = 2;
+
+const BASE32: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const CONSONANTS: &[u8; 16] = b"bdfghjklmnprstvz";
+const VOWELS: &[u8; 4] = b"aiou";
+
+impl Generator {
+    /// Backup code of a passphrase drawn from this generator, in groups of
+    /// four base32 characters
+    ///
+    /// The code does not contain the password, but with these settings it
+    /// gives it back through [`from_backup`](Self::from_backup). The
+    /// built-in corpora are public and the settings easily guessed, so the
+    /// code is as sensitive as the password itself. Returns
+    /// [`Error::UnknownEntry`] if a segment does not come from a corpus of
+    /// this generator.
+    pub fn backup_code(&self, passphrase: &Passphrase) -> Result<String, Error> {
+        let chars = base32_encode(&self.backup_bytes(passphrase)?);
+        let groups: Vec<String> = chars.chunks(4).map(|group| group.iter().collect()).collect();
+        Ok(groups.join("-"))
+    }
+
+    /// The same backup as [`backup_code`](Self::backup_code), as
+    /// pronounceable words that are easier to write down or read out
+    pub fn backup_words(&self, passphrase: &Passphrase) -> Result<String, Error> {
+        let words: Vec<String> = self.backup_bytes(passphrase)?
+            .chunks(2)
+            .map(|pair| proquint(u16::from_be_bytes([pair[0], pair.get(1).copied().unwrap_or(0)])))
+            .collect();
+        Ok(words.join("-"))
+    }
+
+    /// Rebuild the exact passphrase from a backup code or backup words
+    ///
+    /// Returns [`Error::InvalidBackup`] for a mistyped code and
+    /// [`Error::BackupMismatch`] if it was made with other corpora, modes
+    /// or separator.
+    pub fn from_backup(&self, code: &str) -> Result<Passphrase, Error> {
+        let bytes = parse_words(code).or_else(|| base32_decode(code)).ok_or(Error::InvalidBackup)?;
+        let backup = Backup::read(&bytes).filter(|backup| backup.segments.len() == 2).ok_or(Error::InvalidBackup)?;
+        if backup.settings != self.settings_hash() {
+            return Err(Error::BackupMismatch);
+        }
+
+        let mut parts = Vec::with_capacity(backup.segments.len());
+        let mut origins = Vec::with_capacity(backup.segments.len());
+        for (i, &(position, index)) in backup.segments.iter().enumerate() {
+            let corpora = self.part_corpora(i);
+            let corpus = corpora.get(position).ok_or(Error::BackupMismatch)?;
+            let entry = corpus.entry(index)
+                .ok_or_else(|| Error::UnknownEntry { corpus: corpus.name().to_string(), index })?;
+            parts.push(entry);
+            origins.push((corpus.name().to_string(), index));
+        }
+        let passphrase = self.assemble(&parts, origins, backup.number, self.entropy_bits());
+        if backup.capitals.iter().any(|&i| i >= passphrase.password.chars().count()) {
+            return Err(Error::InvalidBackup);
+        }
+
+        Ok(with_capitals(passphrase, backup.capitals))
+    }
+
+    fn backup_bytes(&self, passphrase: &Passphrase) -> Result<Vec<u8>, Error> {
+        let segments = passphrase.segments.iter().enumerate()
+            .map(|(i, segment)| {
+                self.part_corpora(i).iter()
+                    .position(|corpus| corpus.name() == segment.corpus && corpus.entry(segment.index) == Some(&segment.entry))
+                    .map(|position| (position, segment.index))
+                    .ok_or_else(|| Error::UnknownEntry { corpus: segment.corpus.clone(), index: segment.index })
+            })
+            .collect::<Result<_, Error>>()?;
+        let backup = Backup {
+            settings: self.settings_hash(),
+            segments,
+            number: passphrase.number,
+            capitals: passphrase.capitals.clone(),
+        };
+        Ok(backup.write())
+    }
+
+    /// The corpora of the front part for `i == 0`, of the back part otherwise
+    fn part_corpora(&self, i: usize) -> Vec<&Corpus> {
+        let corpora = if i == 0 { self.source.front_corpora() } else { self.source.back_corpora() };
+        corpora.into_iter().map(|(corpus, _)| corpus).collect()
+    }
+
+    /// 32-bit FNV-1a hash of everything that turns entries into a password
+    ///
+    /// Covers the separator, the mode of each part and the name and entries
+    /// of its corpora, but not the sampling settings, which only decide
+    /// what is drawn.
+    fn settings_hash(&self) -> u32 {
+        let mut hash = Fnv::new();
+        hash.write(self.separator.as_bytes());
+        let (front_mode, back_mode) = self.modes();
+        for (i, mode) in [front_mode, back_mode].into_iter().enumerate() {
+            hash.write(mode.name().as_bytes());
+            for corpus in self.part_corpora(i) {
+                hash.write(corpus.name().as_bytes());
+                for index in 0..corpus.len() {
+                    let Entry { text, pinyin, .. } = corpus.entry(index).expect("index below len");
+                    hash.write(text.as_bytes());
+                    hash.write(pinyin.as_deref().unwrap_or_default().as_bytes());
+                }
+            }
+        }
+        hash.0
+    }
+}
+
+/// What a backup code holds
+struct Backup {
+    settings: u32,
+    /// Position of the corpus among those of the part, and index of the entry
+    segments: Vec<(usize, usize)>,
+    number: Option<u32>,
+    capitals: Vec<usize>,
+}
+
+impl Backup {
+    fn write(&self) -> Vec<u8> {
+        let mut bytes = vec![BACKUP_VERSION];
+        bytes.extend(self.settings.to_be_bytes());
+        write_varint(&mut bytes, self.segments.len() as u64);
+        for &(position, index) in &self.segments {
+            write_varint(&mut bytes, position as u64);
+            write_varint(&mut bytes, index as u64);
+        }
+        write_varint(&mut bytes, self.number.map_or(0, |number| u64::from(number) + 1));
+        write_varint(&mut bytes, self.capitals.len() as u64);
+        for &capital in &self.capitals {
+            write_varint(&mut bytes, capital as u64);
+        }
+        bytes.extend(crc16(&bytes).to_be_bytes());
+        bytes
+    }
+
+    /// Parse and check `bytes`, which may end in one byte of padding
+    fn read(bytes: &[u8]) -> Option<Backup> {
+        let mut reader = Reader { bytes, at: 0 };
+        if reader.byte()? != BACKUP_VERSION {
+            return None;
+        }
+        let settings = u32::from_be_bytes([reader.byte()?, reader.byte()?, reader.byte()?, reader.byte()?]);
+        let segments = (0..reader.small()?)
+            .map(|_| Some((reader.small()?, reader.small()?)))
+            .collect::<Option<Vec<_>>>()?;
+        let number = match reader.varint()? {
+            0 => None,
+            number => Some(u32::try_from(number - 1).ok()?),
+        };
+        let capitals = (0..reader.small()?).map(|_| reader.small()).collect::<Option<Vec<_>>>()?;
+
+        let (content, rest) = bytes.split_at(reader.at);
+        let checksum = rest.get(..2)?;
+        let padding = &rest[2..];
+        if checksum != crc16(content).to_be_bytes() || !matches!(padding, [] | [0]) {
+            return None;
+        }
+        Some(Backup { settings, segments, number, capitals })
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Option<u8> {
+        let byte = *self.bytes.get(self.at)?;
+        self.at += 1;
+        Some(byte)
+    }
+
+    /// LEB128: seven bits per byte, low bits first, the high bit set on all
+    /// but the last byte
+    fn varint(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    /// A count, position or index, which no sensible code has above 2^24
+    fn small(&mut self) -> Option<usize> {
+        self.varint().filter(|&value| value < 1 << 24).map(|value| value as usize)
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// CRC-16/CCITT-FALSE
+fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc = 0xffffu16;
+    for &byte in bytes {
+        crc ^= u16::from(byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+struct Fnv(u32);
+
+impl Fnv {
+    fn new() -> Self {
+        Fnv(0x811c_9dc5)
+    }
+
+    /// Hash `bytes` followed by a zero byte, so fields cannot run together
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes.iter().chain([&0]) {
+            self.0 = (self.0 ^ u32::from(byte)).wrapping_mul(0x0100_0193);
+        }
+    }
+}
+
+fn base32_encode(bytes: &[u8]) -> Vec<char> {
+    let mut chars = Vec::with_capacity(bytes.len() * 8 / 5 + 1);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for &byte in bytes {
+        buffer = buffer << 8 | u32::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            chars.push(BASE32[(buffer >> bits & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        chars.push(BASE32[(buffer << (5 - bits) & 31) as usize] as char);
+    }
+    chars
+}
+
+/// Decode base32, ignoring case and dashes and reading I and L as 1 and O
+/// as 0, as Crockford's alphabet does
+///
+/// The bits left over after the last byte must be zero padding, so a typo
+/// in the last character is not ignored.
+fn base32_decode(code: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in code.chars().filter(|&c| c != '-' && !c.is_whitespace()) {
+        let c = match c.to_ascii_uppercase() {
+            'I' | 'L' => '1',
+            'O' => '0',
+            c => c,
+        };
+        let value = BASE32.iter().position(|&b| b as char == c)?;
+        buffer = buffer << 5 | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes).filter(|_| bits < 5 && buffer & ((1 << bits) - 1) == 0)
+}
+
+/// A proquint: consonant, vowel, consonant, vowel, consonant for 4, 2, 4, 2
+/// and 4 bits
+fn proquint(value: u16) -> String {
+    let value = usize::from(value);
+    [
+        CONSONANTS[value >> 12 & 15],
+        VOWELS[value >> 10 & 3],
+        CONSONANTS[value >> 6 & 15],
+        VOWELS[value >> 4 & 3],
+        CONSONANTS[value & 15],
+    ]
+    .iter()
+    .map(|&b| b as char)
+    .collect()
+}
+
+/// Bytes of proquints separated by dashes or whitespace; `None` if any
+/// word is not one
+fn parse_words(code: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    for word in code.split(|c: char| c == '-' || c.is_whitespace()).filter(|word| !word.is_empty()) {
+        let word = word.to_ascii_lowercase();
+        let [c1, v1, c2, v2, c3] = <[u8; 5]>::try_from(word.as_bytes()).ok()?;
+        let consonant = |c| CONSONANTS.iter().position(|&b| b == c);
+        let vowel = |v| VOWELS.iter().position(|&b| b == v);
+        let value = consonant(c1)? << 12 | vowel(v1)? << 10 | consonant(c2)? << 6 | vowel(v2)? << 4 | consonant(c3)?;
+        bytes.extend((value as u16).to_be_bytes());
+    }
+    Some(bytes).filter(|bytes| !bytes.is_empty())
+}
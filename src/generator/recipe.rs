@@ -0,0 +1,125 @@
+//! Versioned serde schema of [`Generator`]
+//!
+//! A generator serializes as a recipe of its settings, e.g. in JSON:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "source": {"pair": ["chengyu", "poetry"]},
+//!   "mode": {"dual": {"front": "pinyin_full", "back": "pinyin_init"}},
+//!   "separator": "-",
+//!   "number": true,
+//!   "random_capitalize": false,
+//!   "meaning_hint": false,
+//!   "length": [4, 7],
+//!   "tier_weights": [4.0, 2.0, 1.0],
+//!   "distinct": true,
+//!   "min_entropy": 60.0
+//! }
+//! ```
+//!
+//! Fields other than `version` may be left out and take the defaults of
+//! [`Generator::new`]. Unknown fields, recipes of a newer version and
+//! settings [`Generator::build`] rejects for any corpus, such as an empty
+//! length range, are errors rather than a generator that panics or differs.
+
+use super::Generator;
+use crate::provider::{Sampling, Source};
+use crate::transform::TransformMode;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Version of the recipe schema written by this crate
+pub const RECIPE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Recipe {
+    version: u32,
+    source: Source,
+    mode: TransformMode,
+    separator: String,
+    number: bool,
+    random_capitalize: bool,
+    meaning_hint: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    length: Option<[usize; 2]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tier_weights: Option<[f64; 3]>,
+    distinct: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_entropy: Option<f64>,
+}
+
+impl Default for Recipe {
+    /// The settings of `Generator::new()`, without a version
+    fn default() -> Self {
+        Recipe {
+            version: 0,
+            ..Recipe::from(&Generator::new())
+        }
+    }
+}
+
+impl From<&Generator> for Recipe {
+    fn from(generator: &Generator) -> Self {
+        Recipe {
+            version: RECIPE_VERSION,
+            source: generator.source.clone(),
+            mode: generator.mode.clone(),
+            separator: generator.separator.clone(),
+            number: generator.add_number,
+            random_capitalize: generator.random_capitalize,
+            meaning_hint: generator.meaning_hint,
+            length: generator.sampling.length.as_ref().map(|length| [*length.start(), *length.end()]),
+            tier_weights: generator.sampling.tier_weights,
+            distinct: generator.distinct,
+            min_entropy: generator.min_entropy,
+        }
+    }
+}
+
+impl From<Recipe> for Generator {
+    fn from(recipe: Recipe) -> Self {
+        Generator {
+            source: recipe.source,
+            mode: recipe.mode,
+            separator: recipe.separator,
+            add_number: recipe.number,
+            random_capitalize: recipe.random_capitalize,
+            meaning_hint: recipe.meaning_hint,
+            sampling: Sampling {
+                length: recipe.length.map(|[min, max]| min..=max),
+                tier_weights: recipe.tier_weights,
+            },
+            distinct: recipe.distinct,
+            min_entropy: recipe.min_entropy,
+            cache: Default::default(),
+        }
+    }
+}
+
+impl Serialize for Generator {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Recipe::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Generator {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let recipe = Recipe::deserialize(deserializer)?;
+        match recipe.version {
+            0 => return Err(D::Error::missing_field("version")),
+            1..=RECIPE_VERSION => {}
+            version => {
+                return Err(D::Error::custom(format!(
+                    "unsupported recipe version {}, this crate reads up to {}",
+                    version, RECIPE_VERSION
+                )))
+            }
+        }
+        let generator = Generator::from(recipe);
+        generator.check_settings().map_err(D::Error::custom)?;
+        Ok(generator)
+    }
+}
@@ -1,10 +1,19 @@
+pub mod corpus;
+pub mod error;
+pub mod entropy;
 pub mod generator;
+pub mod passphrase;
 pub mod provider;
 pub mod transform;
 
 include!(concat!(env!("OUT_DIR"), "/data.rs"));
 
-pub use generator::Generator;
+pub use corpus::{Corpus, Entry, Tier};
+pub use error::Error;
+pub use generator::{BatchError, BatchOptions, Generator, BACKUP_VERSION};
+#[cfg(feature = "serde")]
+pub use generator::RECIPE_VERSION;
+pub use passphrase::{Passphrase, Segment};
 pub use provider::Source;
 pub use transform::{Mode, DualMode, TransformMode};
 
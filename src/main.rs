@@ -1,121 +1,1140 @@
-use poetry_pass::{Generator, generate};
+mod clipboard;
+mod messages;
+#[cfg(feature = "cli")]
+mod picker;
+#[cfg(feature = "cli")]
+mod profile;
+
+use messages::{fill, Lang, Messages};
+#[cfg(feature = "cli")]
+use profile::{Profile, Profiles, Value};
+use poetry_pass::corpus::validate;
+use poetry_pass::{BatchError, BatchOptions, Corpus, Entry, Error, Generator, Mode, Passphrase, Source, Tier};
 use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+#[cfg(feature = "cli")]
+use std::path::PathBuf;
+use std::process;
+
+/// How generated passwords are printed
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Plain,
+    /// One JSON object per line
+    #[cfg(feature = "cli")]
+    Json,
+    /// Tab-separated columns with a header line
+    Tsv,
+}
 
 struct Config {
+    show_help: bool,
     show_source: bool,
+    show_entropy: bool,
     count: Option<usize>,
+    format: Format,
+    /// Seconds after which a password copied with `--copy` is cleared, or
+    /// `None` to print it
+    copy: Option<u64>,
+    /// Choose from pages of candidates; `count` is the page size
+    pick: bool,
+    /// Show the backup code of the password
+    backup: bool,
+    batch: BatchOptions,
     generator: Generator,
 }
 
+/// Seconds a copied password stays on the clipboard by default
+const COPY_TIMEOUT: u64 = 45;
+
+/// A command line error and the exit status it should produce
+struct ArgError {
+    message: String,
+    status: i32,
+}
+
+impl ArgError {
+    fn usage(message: String) -> Self {
+        ArgError { message, status: 2 }
+    }
+}
+
+/// Generator options that take a value
+const VALUE_OPTIONS: &[&str] = &[
+    "-n", "--count", "--format", "--separator", "--sources", "--modes", "--corpus", "--length",
+    "--union", "--weighted", "--tier", "--tier-weights", "--min-entropy", "--copy-timeout",
+    "--profile", "--config",
+];
+
+/// Split the arguments into options and their values
+///
+/// Accepts `--flag value`, `--flag=value`, clusters of short flags such as
+/// `-dr`, and a value attached to the last short flag as in `-n5` or `-dn 5`.
+/// Arguments that are not options are returned as they are.
+fn tokenize(args: &[String], value_options: &[&str], t: &Messages) -> Result<Vec<(String, Option<String>)>, ArgError> {
+    let mut tokens = Vec::new();
+    let mut args = args.iter();
+    
+    while let Some(arg) = args.next() {
+        let mut options = Vec::new();
+        if let Some(long) = arg.strip_prefix("--") {
+            match long.split_once('=') {
+                Some((name, value)) => options.push((format!("--{}", name), Some(value.to_string()))),
+                None => options.push((arg.clone(), None)),
+            }
+        } else if let Some(cluster) = arg.strip_prefix('-').filter(|cluster| !cluster.is_empty()) {
+            for (i, ch) in cluster.char_indices() {
+                let flag = format!("-{}", ch);
+                let rest = &cluster[i + ch.len_utf8()..];
+                // `=` ends the cluster even after a flag, which then rejects the value
+                if !rest.is_empty() && (value_options.contains(&flag.as_str()) || rest.starts_with('=')) {
+                    let value = rest.strip_prefix('=').unwrap_or(rest);
+                    options.push((flag, Some(value.to_string())));
+                    break;
+                }
+                options.push((flag, None));
+            }
+        } else {
+            tokens.push((arg.clone(), None));
+            continue;
+        }
+        
+        for (name, value) in options {
+            let takes_value = value_options.contains(&name.as_str());
+            let value = match value {
+                Some(_) if !takes_value => {
+                    return Err(ArgError::usage(fill(t.takes_no_value, &[&name])));
+                }
+                None if takes_value => match args.next() {
+                    Some(value) => Some(value.clone()),
+                    None => return Err(ArgError::usage(fill(t.needs_value, &[&name]))),
+                },
+                value => value,
+            };
+            tokens.push((name, value));
+        }
+    }
+    
+    Ok(tokens)
+}
+
 impl Config {
     fn new() -> Self {
         Config {
+            show_help: false,
             show_source: false,
+            show_entropy: false,
             count: None,
+            format: Format::Plain,
+            copy: None,
+            pick: false,
+            backup: false,
+            batch: BatchOptions::default(),
             generator: Generator::new(),
         }
     }
     
-    fn parse_args(args: &[String]) -> Self {
-        let mut config = Config::new();
-        let mut i = 1;
+    fn parse_args(args: &[String], t: &Messages) -> Result<Self, ArgError> {
+        match tokenize(&args[1..], VALUE_OPTIONS, t) {
+            Ok(options) => Self::from_options(options, t),
+            Err(_) if args[1..].iter().any(|arg| arg == "-h" || arg == "--help") => Ok(Config::help()),
+            Err(e) => Err(e),
+        }
+    }
+    
+    /// The configuration that only shows the help
+    fn help() -> Self {
+        Config { show_help: true, ..Config::new() }
+    }
+    
+    /// Build the configuration from the selected profile, then the options
+    ///
+    /// `-h` anywhere shows the help, whatever the other options are.
+    fn from_options(mut options: Vec<(String, Option<String>)>, t: &Messages) -> Result<Self, ArgError> {
+        if options.iter().any(|(name, _)| name == "-h" || name == "--help") {
+            return Ok(Config::help());
+        }
+        
+        let mut config = Config::from_profile(&mut options, t)?.apply(options, t)?;
+        if !config.show_help {
+            config.generator = config.generator.build().map_err(|e| generator_error(&e, t))?;
+        }
+        Ok(config)
+    }
+    
+    /// The configuration of the selected profile, taking `--config`,
+    /// `--profile` and `--no-profile` out of `options`
+    #[cfg(feature = "cli")]
+    fn from_profile(options: &mut Vec<(String, Option<String>)>, t: &Messages) -> Result<Self, ArgError> {
+        let config_path = take_option(options, "--config");
+        let profile_name = take_option(options, "--profile");
+        let no_profile = options.iter().any(|(name, _)| name == "--no-profile");
+        options.retain(|(name, _)| name != "--no-profile");
         
-        while i < args.len() {
-            match args[i].as_str() {
+        let profile = if no_profile { None } else { load_profile(config_path, profile_name, t)? };
+        match profile {
+            Some((name, profile)) => profile_options(&profile, t)
+                .and_then(|profile_options| Config::new().apply(profile_options, t))
+                .map_err(|e| ArgError { message: fill(t.in_profile, &[&name, &e.message]), ..e }),
+            None => Ok(Config::new()),
+        }
+    }
+    
+    /// Without the `cli` feature there are no profiles, and their options
+    /// are unknown
+    #[cfg(not(feature = "cli"))]
+    fn from_profile(_options: &mut Vec<(String, Option<String>)>, _t: &Messages) -> Result<Self, ArgError> {
+        Ok(Config::new())
+    }
+    
+    /// Apply options in order, so later ones override earlier ones
+    fn apply(self, options: Vec<(String, Option<String>)>, t: &Messages) -> Result<Self, ArgError> {
+        let mut config = self;
+        
+        for (name, value) in options {
+            let value = value.unwrap_or_default();
+            let invalid = |expected: &str| ArgError::usage(fill(t.invalid_value, &[&name, &expected, &value]));
+            let generator = config.generator;
+            
+            config.generator = match name.as_str() {
+                "-h" | "--help" => {
+                    config.show_help = true;
+                    config.generator = generator;
+                    return Ok(config);
+                }
                 "-s" | "--source" => {
                     config.show_source = true;
+                    generator
+                }
+                "-e" | "--entropy" => {
+                    config.show_entropy = true;
+                    generator
                 }
                 "-n" | "--count" => {
-                    if i + 1 < args.len() {
-                        config.count = args[i + 1].parse().ok();
-                        i += 1; // Skip the count value
-                    }
+                    let count = value.parse().ok().filter(|&count| count > 0);
+                    config.count = Some(count.ok_or_else(|| invalid(t.expect_count))?);
+                    generator
                 }
-                "-i" | "--initials" => {
-                    config.generator = config.generator.initials();
+                "--copy" => {
+                    config.copy = Some(config.copy.unwrap_or(COPY_TIMEOUT));
+                    generator
                 }
-                "-c" | "--chinese" => {
-                    config.generator = config.generator.chinese();
+                "--copy-timeout" => {
+                    config.copy = Some(value.parse().map_err(|_| invalid(t.expect_seconds))?);
+                    generator
                 }
-                "-d" | "--dual" => {
-                    config.generator = config.generator.front_full_back_init();
+                #[cfg(feature = "cli")]
+                "--pick" => {
+                    config.pick = true;
+                    generator
                 }
-                "--dual-reverse" => {
-                    config.generator = config.generator.front_init_back_full();
+                "--backup" => {
+                    config.backup = true;
+                    generator
                 }
-                "-r" | "--random-caps" => {
-                    config.generator = config.generator.random_capitalize();
+                #[cfg(feature = "cli")]
+                "--json" => {
+                    config.format = Format::Json;
+                    generator
                 }
-                "--word-poem" => {
-                    config.generator = config.generator.words_poetry();
+                "--format" => {
+                    config.format = parse_format(&value).ok_or_else(|| invalid(t.expect_format))?;
+                    generator
                 }
-                "--poem-word" => {
-                    config.generator = config.generator.poetry_words();
+                "--distinct-lines" => {
+                    config.batch.distinct_lines = true;
+                    generator
                 }
-                "--word-word" => {
-                    config.generator = config.generator.words_words();
+                "-i" | "--initials" => generator.initials(),
+                "--full-pinyin" => generator.full_pinyin(),
+                "-c" | "--chinese" => generator.chinese(),
+                "--zhuyin" => generator.zhuyin(),
+                "-d" | "--dual" => generator.front_full_back_init(),
+                "--dual-reverse" => generator.front_init_back_full(),
+                "--modes" => {
+                    let modes = value.split_once(',').and_then(|(front, back)| Some((parse_mode(front)?, parse_mode(back)?)));
+                    let (front, back) = modes.ok_or_else(|| invalid(t.expect_modes))?;
+                    generator.dual_mode(front, back)
                 }
-                "--poem-poem" => {
-                    config.generator = config.generator.poetry_poetry();
+                "-r" | "--random-caps" => generator.random_capitalize(),
+                "--no-random-caps" => generator.no_random_capitalize(),
+                "--separator" => generator.separator(&value),
+                "--no-number" => generator.no_number(),
+                "--number" => generator.with_number(),
+                "--poetry" => generator.poetry_only(),
+                "--words" => generator.words_only(),
+                "--word-poem" => generator.words_poetry(),
+                "--poem-word" => generator.poetry_words(),
+                "--word-word" => generator.words_words(),
+                "--poem-poem" => generator.poetry_poetry(),
+                "--chengyu" => generator.chengyu_only(),
+                "--song-ci" => generator.song_ci_only(),
+                "--yuan-qu" => generator.yuan_qu_only(),
+                "--lunyu" => generator.lun_yu_only(),
+                "--daodejing" => generator.dao_de_jing_only(),
+                "--shijing" => generator.shi_jing_only(),
+                "--guwen" => generator.gu_wen_only(),
+                "--sources" => {
+                    let sources = value.split_once(',').and_then(|(front, back)| Some((parse_source(front)?, parse_source(back)?)));
+                    let (front, back) = sources.ok_or_else(|| invalid(t.expect_sources))?;
+                    generator.sources(front, back)
                 }
-                _ => {} // Ignore unknown arguments
-            }
-            i += 1;
+                "--length" => {
+                    let (min, max) = parse_length(&value).ok_or_else(|| invalid(t.expect_length))?;
+                    generator.segment_length(min, max)
+                }
+                "--union" => {
+                    let sources: Option<Vec<Source>> = value.split(',').map(parse_source).collect();
+                    generator.union(sources.ok_or_else(|| invalid(t.expect_union))?)
+                }
+                "--weighted" => {
+                    let sources: Option<Vec<(Source, f64)>> = value
+                        .split(',')
+                        .map(|item| {
+                            let (name, weight) = item.split_once('=')?;
+                            let weight: f64 = weight.trim().parse().ok()?;
+                            (weight.is_finite() && weight >= 0.0).then_some((parse_source(name)?, weight))
+                        })
+                        .collect();
+                    match sources {
+                        Some(sources) if sources.iter().any(|&(_, weight)| weight > 0.0) => generator.weighted(sources),
+                        _ => return Err(invalid(t.expect_weighted)),
+                    }
+                }
+                "--tier" => generator.max_tier(parse_tier(&value).ok_or_else(|| invalid(t.expect_tier))?),
+                "--tier-weights" => {
+                    let [famous, common, obscure] = parse_weights(&value).ok_or_else(|| invalid(t.expect_tier_weights))?;
+                    generator.tier_weights(famous, common, obscure)
+                }
+                "--all-tiers" => generator.all_tiers(),
+                "--min-entropy" => {
+                    let bits = value.parse().ok().filter(|bits: &f64| bits.is_finite() && *bits >= 0.0);
+                    generator.min_entropy(bits.ok_or_else(|| invalid(t.expect_min_entropy))?)
+                }
+                "--allow-repeats" => generator.allow_repeats(),
+                "--distinct" => generator.forbid_repeats(),
+                "--meaning" => generator.meaning_hint(),
+                "--no-meaning" => generator.no_meaning_hint(),
+                "--corpus" => match Corpus::load(&value) {
+                    Ok(corpus) => generator.corpus(corpus),
+                    Err(e) => {
+                        return Err(ArgError { message: fill(t.cannot_read_corpus, &[&value, &e]), status: 1 });
+                    }
+                },
+                _ if name.starts_with('-') => return Err(ArgError::usage(fill(t.unknown_option, &[&name]))),
+                _ => return Err(ArgError::usage(fill(t.unexpected_argument, &[&name]))),
+            };
         }
         
-        config
-    }
-}
-
-fn print_help() {
-    println!("诗词密码生成器");
-    println!("用法: poetry-pass [选项]");
-    println!("选项:");
-    println!("  -i, --initials       使用拼音首字母");
-    println!("  -c, --chinese        使用中文原文");
-    println!("  -d, --dual           双模式：前全拼后首字母");
-    println!("  --dual-reverse       双模式：前首字母后全拼");
-    println!("  -r, --random-caps    随机大写至少一个字母");
-    println!("  -n, --count N        生成N个密码");
-    println!("  -s, --source         显示密码来源");
-    println!("  --word-poem          词语-诗句组合");
-    println!("  --poem-word          诗句-词语组合");
-    println!("  --word-word          词语-词语组合");
-    println!("  --poem-poem          诗句-诗句组合");
-    println!("  -h, --help           显示帮助");
-    println!();
-    println!("示例:");
-    println!("  poetry-pass -d       # 生成如 huaduo-hlzdc 格式");
-    println!("  poetry-pass --dual-reverse  # 生成如 hd-huaduolizidanci 格式");
-    println!("  poetry-pass --word-poem     # 生成如 huaduo-yuelangxingxi1234 格式");
-    println!("  poetry-pass --poem-word     # 生成如 yuelangxingxi-huaduo1234 格式");
-    println!("  poetry-pass --word-poem -s  # 显示词语-诗句密码及来源");
+        Ok(config)
+    }
+}
+
+/// Remove every occurrence of a value option, returning the last value
+#[cfg(feature = "cli")]
+fn take_option(options: &mut Vec<(String, Option<String>)>, name: &str) -> Option<String> {
+    let mut value = None;
+    options.retain(|(option, option_value)| {
+        let matches = option == name;
+        if matches {
+            value = option_value.clone();
+        }
+        !matches
+    });
+    value
+}
+
+/// Read the profile named by `--profile`, or the file's default profile
+///
+/// A missing configuration file, or no place to look for one when neither
+/// `XDG_CONFIG_HOME` nor `HOME` is set, is only an error when `--config` or
+/// `--profile` asks for it.
+#[cfg(feature = "cli")]
+fn load_profile(
+    path: Option<String>,
+    name: Option<String>,
+    t: &Messages,
+) -> Result<Option<(String, Profile)>, ArgError> {
+    let explicit = path.is_some() || name.is_some();
+    let path = match path.map(PathBuf::from).or_else(profile::default_path) {
+        Some(path) => path,
+        None if !explicit => return Ok(None),
+        None => return Err(ArgError { message: t.no_config_location.to_string(), status: 1 }),
+    };
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(_) if !explicit => return Ok(None),
+        Err(e) => {
+            return Err(ArgError { message: fill(t.cannot_read_config, &[&path.display(), &e]), status: 1 });
+        }
+    };
+    
+    let profiles = Profiles::parse(&text).map_err(|e| {
+        let line = e.span().map_or(1, |span| text[..span.start].matches('\n').count() + 1);
+        ArgError::usage(fill(t.config_invalid, &[&path.display(), &line, &e.message().replace('\n', ": ")]))
+    })?;
+    let Some(name) = name.or(profiles.default_profile.clone()) else {
+        return Ok(None);
+    };
+    match profiles.get(&name) {
+        Some(profile) => Ok(Some((name, profile.clone()))),
+        None => Err(ArgError::usage(fill(t.unknown_profile, &[&path.display(), &name]))),
+    }
+}
+
+/// Turn profile settings into options: `true` sets a flag, `false` leaves
+/// it unset, and other values are the argument of the option
+#[cfg(feature = "cli")]
+fn profile_options(profile: &Profile, t: &Messages) -> Result<Vec<(String, Option<String>)>, ArgError> {
+    let mut options = Vec::new();
+    for (key, value) in profile {
+        let name = format!("--{}", key);
+        let takes_value = VALUE_OPTIONS.contains(&name.as_str());
+        match value {
+            Value::Bool(true) if !takes_value => options.push((name, None)),
+            Value::Bool(false) if !takes_value => {}
+            Value::Bool(_) => return Err(ArgError::usage(fill(t.needs_value, &[&name]))),
+            _ if !takes_value => return Err(ArgError::usage(fill(t.takes_no_value, &[&name]))),
+            value => options.push((name, Some(value.to_arg()))),
+        }
+    }
+    Ok(options)
+}
+
+/// Parse a segment length of "4-7" or "5" characters
+fn parse_length(value: &str) -> Option<(usize, usize)> {
+    let (min, max) = value.split_once('-').unwrap_or((value, value));
+    let (min, max) = (min.trim().parse().ok()?, max.trim().parse().ok()?);
+    (min <= max).then_some((min, max))
+}
+
+/// Parse a built-in source name for `--union` and `--weighted`
+fn parse_source(name: &str) -> Option<Source> {
+    match name.trim() {
+        "poetry" | "诗句" | "詩句" => Some(Source::Poetry),
+        "words" | "词语" | "詞語" => Some(Source::Words),
+        "chengyu" | "成语" | "成語" => Some(Source::Chengyu),
+        "song-ci" | "宋词" | "宋詞" => Some(Source::SongCi),
+        "yuan-qu" | "元曲" => Some(Source::YuanQu),
+        "lunyu" | "论语" | "論語" => Some(Source::LunYu),
+        "daodejing" | "道德经" | "道德經" => Some(Source::DaoDeJing),
+        "shijing" | "诗经" | "詩經" => Some(Source::ShiJing),
+        "guwen" | "古文" => Some(Source::GuWen),
+        _ => None,
+    }
+}
+
+fn parse_format(value: &str) -> Option<Format> {
+    match value {
+        "plain" => Some(Format::Plain),
+        #[cfg(feature = "cli")]
+        "json" => Some(Format::Json),
+        "tsv" => Some(Format::Tsv),
+        _ => None,
+    }
+}
+
+fn parse_mode(value: &str) -> Option<Mode> {
+    match value.trim() {
+        "full" | "pinyin_full" | "全拼" => Some(Mode::PinyinFull),
+        "init" | "pinyin_init" | "首字母" => Some(Mode::PinyinInit),
+        "chinese" | "中文" => Some(Mode::Chinese),
+        "zhuyin" | "注音" => Some(Mode::Zhuyin),
+        _ => None,
+    }
+}
+
+fn parse_tier(value: &str) -> Option<Tier> {
+    match value {
+        "famous" | "名句" => Some(Tier::Famous),
+        "common" | "常见" | "常見" => Some(Tier::Common),
+        "obscure" | "生僻" => Some(Tier::Obscure),
+        _ => None,
+    }
+}
+
+/// Parse tier weights of "4,2,1", famous first
+fn parse_weights(value: &str) -> Option<[f64; 3]> {
+    let weights: Vec<f64> = value.split(',').map(|w| w.trim().parse().ok()).collect::<Option<_>>()?;
+    let weights: [f64; 3] = weights.try_into().ok()?;
+    let valid = weights.iter().all(|w| w.is_finite() && *w >= 0.0) && weights.iter().any(|w| *w > 0.0);
+    valid.then_some(weights)
+}
+
+/// Format an entry's author and title as "李白《静夜思》"
+fn origin(entry: &Entry) -> Option<String> {
+    match (&entry.author, &entry.title) {
+        (Some(author), Some(title)) => Some(format!("{}《{}》", author, title)),
+        (Some(author), None) => Some(author.clone()),
+        (None, Some(title)) => Some(format!("《{}》", title)),
+        (None, None) => None,
+    }
+}
+
+/// Print the source hint and the origin of each entry that has one
+fn print_source(generator: &Generator, entries: &[Entry], t: &Messages) {
+    println!("{}", fill(t.source, &[&generator.source_hint(entries)]));
+    for entry in entries {
+        if let Some(origin) = origin(entry) {
+            println!("{}", fill(t.origin, &[&entry.text, &origin]));
+        }
+    }
+}
+
+/// Put the password on the clipboard, show where it comes from, and have
+/// it cleared after `seconds` unless that is 0
+///
+/// A part in Chinese is its own source hint, so then only the authors and
+/// titles are shown, keeping the password out of the scrollback.
+fn copy_password(generator: &Generator, passphrase: &Passphrase, seconds: u64, t: &Messages) {
+    let password = passphrase.password.as_str();
+    let backend = match clipboard::copy(password) {
+        Ok(backend) => backend,
+        Err(e) => {
+            eprintln!("{}", fill(t.copy_failed, &[&e]));
+            process::exit(1);
+        }
+    };
+    if passphrase.segments.iter().any(|segment| segment.mode == Mode::Chinese) {
+        for origin in passphrase.segments.iter().filter_map(|segment| origin(&segment.entry)) {
+            println!("{}", fill(t.origin_only, &[&origin]));
+        }
+    } else {
+        print_source(generator, &passphrase.entries(), t);
+    }
+    if seconds == 0 {
+        eprintln!("{}", t.copied);
+    } else if let Err(e) = clipboard::clear_later(backend, password, seconds) {
+        eprintln!("{}", fill(t.copy_not_cleared, &[&e]));
+    } else {
+        eprintln!("{}", fill(t.copied_until, &[&seconds]));
+    }
+}
+
+/// Show the backup code and words that `recover` turns back into the
+/// password
+fn print_backup(generator: &Generator, passphrase: &Passphrase, t: &Messages) {
+    match (generator.backup_code(passphrase), generator.backup_words(passphrase)) {
+        (Ok(code), Ok(words)) => {
+            println!("{}", fill(t.backup_code, &[&code]));
+            println!("{}", fill(t.backup_words, &[&words]));
+        }
+        (Err(e), _) | (_, Err(e)) => eprintln!("{}", generator_error(&e, t).message),
+    }
+}
+
+fn mode_label(mode: &Mode, t: &Messages) -> &'static str {
+    match mode {
+        Mode::Chinese => t.mode_chinese,
+        Mode::PinyinFull => t.mode_full,
+        Mode::PinyinInit => t.mode_init,
+        Mode::Zhuyin => t.mode_zhuyin,
+    }
+}
+
+/// Describe a batch error in the interface language
+fn batch_error(error: &BatchError, t: &Messages) -> String {
+    match error {
+        BatchError::TooMany { requested, available } => fill(t.batch_too_many, &[requested, available]),
+        BatchError::Exhausted { requested, generated } => fill(t.batch_exhausted, &[requested, generated]),
+    }
+}
+
+/// Describe why the generator cannot be used in the interface language;
+/// settings that are wrong by themselves are usage errors
+fn generator_error(error: &Error, t: &Messages) -> ArgError {
+    let message = match error {
+        Error::InvalidSeparator(separator) => return ArgError::usage(fill(t.invalid_separator, &[separator])),
+        Error::InvalidLength { min, max } => return ArgError::usage(fill(t.invalid_length, &[min, max])),
+        Error::InvalidWeights => return ArgError::usage(t.invalid_weights.to_string()),
+        Error::EmptyCorpus { corpora } => fill(t.empty_corpus, &[corpora]),
+        Error::NoCandidates { corpora } => fill(t.no_candidates, &[corpora]),
+        Error::NoDistinctBack { front, corpora } => fill(t.no_distinct_back, &[corpora, front]),
+        Error::UnknownEntry { corpus, index } => fill(t.unknown_entry, &[corpus, index]),
+        Error::InvalidBackup => t.invalid_backup.to_string(),
+        Error::BackupMismatch => t.backup_mismatch.to_string(),
+        Error::InsufficientEntropy { bits, required } => {
+            fill(t.insufficient_entropy, &[&format!("{:.1}", bits), &format!("{:.1}", required)])
+        }
+        Error::Batch(e) => batch_error(e, t),
+    };
+    ArgError { message, status: 1 }
+}
+
+/// Take `--lang LANG` or `--lang=LANG` out of the arguments
+fn take_lang(args: &mut Vec<String>) -> Result<Option<Lang>, String> {
+    let Some(i) = args.iter().position(|arg| arg == "--lang" || arg.starts_with("--lang=")) else {
+        return Ok(None);
+    };
+    let arg = args.remove(i);
+    let value = match arg.strip_prefix("--lang=") {
+        Some(value) => value.to_string(),
+        None if i < args.len() => args.remove(i),
+        None => String::new(),
+    };
+    Lang::parse(&value).map(Some).ok_or(value)
+}
+
+fn run_validate(args: &[String], t: &Messages) -> i32 {
+    let mut file = None;
+    let mut output = None;
+    let mut show_collisions = false;
+    let mut i = 0;
+
+    while i < args.len() {
+        match args[i].as_str() {
+            "-o" | "--output" if i + 1 < args.len() => {
+                output = Some(&args[i + 1]);
+                i += 1;
+            }
+            "--collisions" => show_collisions = true,
+            option if option.starts_with('-') => {
+                eprintln!("{}", fill(t.unknown_option, &[&option]));
+                eprintln!("{}", t.validate_usage);
+                return 2;
+            }
+            path => file = Some(path),
+        }
+        i += 1;
+    }
+
+    let Some(file) = file else {
+        eprintln!("{}", t.validate_usage);
+        return 2;
+    };
+    let content = match fs::read_to_string(file) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("{}", fill(t.cannot_read_corpus, &[&file, &e]));
+            return 1;
+        }
+    };
+
+    let report = validate::validate(content.lines());
+    for rejected in &report.rejected {
+        println!("{}", fill(t.validate_rejected, &[&file, &rejected.line_no, &rejected.line, &rejected.ch]));
+    }
+    for duplicate in &report.duplicates {
+        println!("{}", fill(t.validate_duplicate, &[&file, duplicate]));
+    }
+    if show_collisions {
+        for collision in &report.collisions {
+            let lines = collision.lines.join(t.list_separator);
+            println!("{}", fill(t.validate_collision, &[&collision.initials, &lines]));
+        }
+    }
+    println!(
+        "{}",
+        fill(
+            t.validate_summary,
+            &[&report.lines.len(), &report.duplicates.len(), &report.rejected.len(), &report.collisions.len()],
+        )
+    );
+
+    if let Some(output) = output {
+        let mut cleaned = report.lines.join("\n");
+        cleaned.push('\n');
+        if let Err(e) = fs::write(output, cleaned) {
+            eprintln!("{}", fill(t.cannot_write, &[&output, &e]));
+            return 1;
+        }
+    }
+
+    if report.rejected.is_empty() { 0 } else { 1 }
+}
+
+#[cfg(feature = "import")]
+fn run_extract(args: &[String], t: &Messages) -> i32 {
+    use poetry_pass::corpus::extract::{self, LineOptions, WordOptions};
+
+    let mut positional = Vec::new();
+    let mut output = None;
+    let mut min = None;
+    let mut max = None;
+    let mut level = None;
+    let mut i = 0;
+
+    while i < args.len() {
+        let arg = args[i].as_str();
+        match arg {
+            "-o" | "--output" | "--min" | "--max" | "--level" => {
+                let Some(value) = args.get(i + 1) else {
+                    eprintln!("{}", fill(t.needs_value, &[&arg]));
+                    return 2;
+                };
+                let invalid = || {
+                    eprintln!("{}", fill(t.invalid_value, &[&arg, &t.expect_number, value]));
+                    2
+                };
+                match arg {
+                    "--min" => match value.parse() {
+                        Ok(n) => min = Some(n),
+                        Err(_) => return invalid(),
+                    },
+                    "--max" => match value.parse() {
+                        Ok(n) => max = Some(n),
+                        Err(_) => return invalid(),
+                    },
+                    "--level" => match value.parse() {
+                        Ok(n) => level = Some(n),
+                        Err(_) => return invalid(),
+                    },
+                    _ => output = Some(value),
+                }
+                i += 1;
+            }
+            _ if arg.starts_with('-') => {
+                eprintln!("{}", fill(t.unknown_option, &[&arg]));
+                eprintln!("{}", t.extract_usage);
+                return 2;
+            }
+            _ => positional.push(arg),
+        }
+        i += 1;
+    }
+
+    let [kind, input] = positional[..] else {
+        eprintln!("{}", t.extract_usage);
+        return 2;
+    };
+    let (default_min, default_max) = match kind {
+        "poetry" => (LineOptions::default().min_chars, LineOptions::default().max_chars),
+        "words" => (WordOptions::default().min_chars, WordOptions::default().max_chars),
+        _ => {
+            eprintln!("{}", t.extract_usage);
+            return 2;
+        }
+    };
+    let (min_chars, max_chars) = (min.unwrap_or(default_min), max.unwrap_or(default_max));
+    if min_chars > max_chars {
+        eprintln!("{}", fill(t.invalid_length, &[&min_chars, &max_chars]));
+        return 2;
+    }
+    let json = match fs::read_to_string(input) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("{}", fill(t.cannot_read, &[&input, &e]));
+            return 1;
+        }
+    };
+
+    let (result, extracted) = if kind == "poetry" {
+        let options = LineOptions { min_chars, max_chars };
+        (extract::poetry_lines(&json, &options), t.extracted_lines)
+    } else {
+        let options = WordOptions { min_chars, max_chars, max_level: level.unwrap_or(WordOptions::default().max_level) };
+        (extract::hsk_words(&json, &options), t.extracted_words)
+    };
+    let lines = match result {
+        Ok(lines) => lines,
+        Err(e) => {
+            eprintln!("{}", fill(t.cannot_parse, &[&input, &e]));
+            return 1;
+        }
+    };
+
+    let mut text = lines.join("\n");
+    text.push('\n');
+    match output {
+        Some(output) => {
+            if let Err(e) = fs::write(output, text) {
+                eprintln!("{}", fill(t.cannot_write, &[&output, &e]));
+                return 1;
+            }
+            eprintln!("{}", fill(extracted, &[&lines.len()]));
+        }
+        None => print!("{}", text),
+    }
+    0
+}
+
+/// Quote a CSV field when it contains a comma, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// `value` rounded to `digits` decimals for JSON output; serde_json writes
+/// NaN and infinities as null
+#[cfg(feature = "cli")]
+fn rounded(value: f64, digits: i32) -> f64 {
+    let scale = 10f64.powi(digits);
+    (value * scale).round() / scale
+}
+
+/// Create `path` with `content`, readable only by the owner on unix, and
+/// refuse to replace an existing file
+fn write_private(path: &str, content: &str) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(content.as_bytes())
+}
+
+/// Replace tabs and line breaks so a TSV field stays in its column
+fn tsv_field(field: &str) -> String {
+    field.replace(['\t', '\n', '\r'], " ")
+}
+
+/// A passphrase as a single-line JSON object
+#[cfg(feature = "cli")]
+fn passphrase_json(passphrase: &Passphrase, hint: &str) -> String {
+    let segments: Vec<serde_json::Value> = passphrase.segments.iter()
+        .map(|segment| serde_json::json!({
+            "source": segment.entry.text,
+            "text": segment.text,
+            "mode": segment.mode.name(),
+            "corpus": segment.corpus,
+            "index": segment.index,
+            "author": segment.entry.author,
+            "title": segment.entry.title,
+        }))
+        .collect();
+    serde_json::json!({
+        "password": passphrase.password,
+        "source": hint,
+        "segments": segments,
+        "number": passphrase.number,
+        "capitals": passphrase.capitals,
+        "separator": passphrase.separator,
+        "mode": passphrase.mode_name(),
+        "entropy": rounded(passphrase.entropy_bits, 2),
+    })
+    .to_string()
+}
+
+/// Print passphrases as JSON lines or TSV rows
+fn print_records(generator: &Generator, passphrases: &[Passphrase], format: Format) {
+    if format == Format::Tsv {
+        println!("password\tsource\tsegments\tmode\tseparator\tentropy");
+    }
+    for passphrase in passphrases {
+        let hint = generator.source_hint(&passphrase.entries());
+        #[cfg(feature = "cli")]
+        if format == Format::Json {
+            println!("{}", passphrase_json(passphrase, &hint));
+            continue;
+        }
+        let segments: Vec<&str> = passphrase.segments.iter().map(|segment| segment.text.as_str()).collect();
+        let row = [
+            passphrase.password.clone(),
+            hint,
+            segments.join(" "),
+            passphrase.mode_name(),
+            passphrase.separator.clone(),
+            format!("{:.2}", passphrase.entropy_bits),
+        ];
+        println!("{}", row.map(|field| tsv_field(&field)).join("\t"));
+    }
+}
+
+/// Turn a backup code back into the password, given the generator options
+/// it was made with
+fn run_recover(args: &[String], t: &Messages) -> i32 {
+    let tokens = match tokenize(args, VALUE_OPTIONS, t) {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            eprintln!("{}", e.message);
+            return e.status;
+        }
+    };
+
+    // Backup words may be given as separate arguments
+    let mut words = Vec::new();
+    let mut generator_options = Vec::new();
+    for (name, value) in tokens {
+        if name.starts_with('-') {
+            generator_options.push((name, value));
+        } else {
+            words.push(name);
+        }
+    }
+    if words.is_empty() {
+        eprintln!("{}", t.recover_usage);
+        return 2;
+    }
+    let code = words.join(" ");
+
+    let config = match Config::from_options(generator_options, t) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e.message);
+            return e.status;
+        }
+    };
+    let passphrase = match config.generator.from_backup(&code) {
+        Ok(passphrase) => passphrase,
+        Err(e) => {
+            eprintln!("{}", generator_error(&e, t).message);
+            return 1;
+        }
+    };
+
+    match config.copy {
+        Some(seconds) => copy_password(&config.generator, &passphrase, seconds, t),
+        None if config.show_source => {
+            println!("{}", fill(t.password, &[&passphrase.password]));
+            print_source(&config.generator, &passphrase.entries(), t);
+        }
+        None => println!("{}", passphrase.password),
+    }
+    0
+}
+
+fn run_provision(args: &[String], t: &Messages) -> i32 {
+    let mut value_options = VALUE_OPTIONS.to_vec();
+    value_options.extend(["-o", "--output"]);
+    let tokens = match tokenize(args, &value_options, t) {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            eprintln!("{}", e.message);
+            return e.status;
+        }
+    };
+
+    let mut input = None;
+    let mut output = None;
+    let mut format = "csv".to_string();
+    let mut generator_options = Vec::new();
+    for (name, value) in tokens {
+        match name.as_str() {
+            "-o" | "--output" => output = value,
+            "--format" => format = value.unwrap_or_default(),
+            _ if input.is_none() && (name == "-" || !name.starts_with('-')) => input = Some(name),
+            _ => generator_options.push((name, value)),
+        }
+    }
+
+    let Some(input) = input else {
+        eprintln!("{}", t.provision_usage);
+        return 2;
+    };
+    if format != "csv" && !(cfg!(feature = "cli") && format == "json") {
+        eprintln!("{}", fill(t.invalid_value, &[&"--format", &t.expect_provision_format, &format]));
+        return 2;
+    }
+
+    let text = if input == "-" {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text).map(|_| text)
+    } else {
+        fs::read_to_string(&input)
+    };
+    let text = match text {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{}", fill(t.cannot_read_users, &[&input, &e]));
+            return 1;
+        }
+    };
+    let mut users: Vec<&str> = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let user = line.trim();
+        if user.is_empty() || user.starts_with('#') {
+            continue;
+        }
+        if users.contains(&user) {
+            eprintln!("{}", fill(t.duplicate_user, &[&(number + 1), &user]));
+            return 1;
+        }
+        users.push(user);
+    }
+
+    let config = match Config::from_options(generator_options, t) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e.message);
+            return e.status;
+        }
+    };
+    let batch = match config.generator.generate_batch(users.len(), &config.batch) {
+        Ok(batch) => batch,
+        Err(e) => {
+            eprintln!("{}", fill(t.provision_failed, &[&users.len(), &batch_error(&e, t)]));
+            return 1;
+        }
+    };
+    let entropy = config.generator.entropy_bits();
+
+    let out = match format.as_str() {
+        #[cfg(feature = "cli")]
+        "json" => {
+            let records: Vec<serde_json::Value> = users.iter().zip(&batch)
+                .map(|(user, passphrase)| serde_json::json!({
+                    "username": user,
+                    "password": passphrase.password,
+                    "source": config.generator.source_hint(&passphrase.entries()),
+                    "entropy": rounded(entropy, 1),
+                }))
+                .collect();
+            let mut out = serde_json::to_string_pretty(&records).expect("JSON values always serialize");
+            out.push('\n');
+            out
+        }
+        _ => {
+            let mut out = String::from("username,password,source,entropy\n");
+            let entropy = format!("{:.1}", entropy);
+            for (user, passphrase) in users.iter().zip(&batch) {
+                let source = config.generator.source_hint(&passphrase.entries());
+                let row = [*user, passphrase.password.as_str(), source.as_str(), entropy.as_str()];
+                out.push_str(&row.map(csv_field).join(","));
+                out.push('\n');
+            }
+            out
+        }
+    };
+
+    match output {
+        Some(output) => {
+            if let Err(e) = write_private(&output, &out) {
+                eprintln!("{}", fill(t.cannot_write, &[&output, &e]));
+                return 1;
+            }
+            eprintln!("{}", fill(t.provisioned, &[&users.len()]));
+        }
+        None => print!("{}", out),
+    }
+    0
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    let lang = match take_lang(&mut args) {
+        Ok(lang) => lang.unwrap_or_else(Lang::from_env),
+        Err(value) => {
+            let t = Lang::from_env().messages();
+            eprintln!("{}", fill(t.invalid_value, &[&"--lang", &t.expect_lang, &value]));
+            process::exit(2);
+        }
+    };
+    let t = lang.messages();
+    
+    if args.get(1).is_some_and(|arg| arg == clipboard::CLEAR_COMMAND) {
+        process::exit(clipboard::run_clear(&args[2..]));
+    }
+    
+    if args.get(1).is_some_and(|arg| arg == "provision") {
+        process::exit(run_provision(&args[2..], t));
+    }
     
-    if args.len() > 1 && (args.contains(&"-h".to_string()) || args.contains(&"--help".to_string())) {
-        print_help();
+    if args.get(1).is_some_and(|arg| arg == "recover") {
+        process::exit(run_recover(&args[2..], t));
+    }
+    
+    if args.get(1).is_some_and(|arg| arg == "validate") {
+        process::exit(run_validate(&args[2..], t));
+    }
+    
+    #[cfg(feature = "import")]
+    if args.get(1).is_some_and(|arg| arg == "extract") {
+        process::exit(run_extract(&args[2..], t));
+    }
+    
+    let config = match Config::parse_args(&args, t) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e.message);
+            if e.status == 2 {
+                eprintln!("{}", t.see_help);
+            }
+            process::exit(e.status);
+        }
+    };
+    
+    if config.show_help {
+        print!("{}", t.help);
         return;
     }
     
-    if args.len() > 1 {
-        let config = Config::parse_args(&args);
-        
-        if let Some(count) = config.count {
-            for password in config.generator.generate_multiple(count) {
-                println!("{}", password);
+    let conflict = if config.pick && config.format != Format::Plain {
+        Some(t.pick_format)
+    } else if config.copy.is_some() && !config.pick && (config.count.is_some() || config.format != Format::Plain) {
+        Some(t.copy_single)
+    } else if config.backup && !config.pick && (config.count.is_some() || config.format != Format::Plain) {
+        Some(t.backup_single)
+    } else {
+        None
+    };
+    if let Some(message) = conflict {
+        eprintln!("{}", message);
+        eprintln!("{}", t.see_help);
+        process::exit(2);
+    }
+    
+    #[cfg(feature = "cli")]
+    if config.pick {
+        // The picker may have switched modes, so the accepted password comes
+        // with the generator that drew it
+        let Some((generator, passphrase)) = picker::run(&config, t) else {
+            process::exit(1);
+        };
+        match config.copy {
+            Some(seconds) => copy_password(&generator, &passphrase, seconds, t),
+            None => println!("{}", passphrase.password),
+        }
+        if config.backup {
+            print_backup(&generator, &passphrase, t);
+        }
+        return;
+    }
+    
+    if let Some(seconds) = config.copy {
+        let passphrase = config.generator.generate_passphrase();
+        copy_password(&config.generator, &passphrase, seconds, t);
+        if config.backup {
+            print_backup(&config.generator, &passphrase, t);
+        }
+    } else if let Some(count) = config.count {
+        match config.generator.generate_batch(count, &config.batch) {
+            Ok(batch) if config.format != Format::Plain => {
+                print_records(&config.generator, &batch, config.format);
             }
-        } else if config.show_source {
-            let (password, source) = config.generator.generate_with_source();
-            println!("密码: {}", password);
-            println!("来源: {}", source);
-        } else {
-            println!("{}", config.generator.generate());
+            Ok(batch) => {
+                for passphrase in batch {
+                    println!("{}", passphrase.password);
+                }
+            }
+            Err(e) => {
+                eprintln!("{}", fill(t.batch_failed, &[&count, &batch_error(&e, t)]));
+                process::exit(1);
+            }
+        }
+    } else if config.format != Format::Plain {
+        let passphrase = config.generator.generate_passphrase();
+        print_records(&config.generator, &[passphrase], config.format);
+    } else if config.show_source {
+        let passphrase = config.generator.generate_passphrase();
+        println!("{}", fill(t.password, &[&passphrase.password]));
+        print_source(&config.generator, &passphrase.entries(), t);
+        for mode in &Mode::ALL {
+            println!("{}", fill(t.rendering, &[&mode_label(mode, t), &passphrase.render_in(mode)]));
         }
+        if config.backup {
+            print_backup(&config.generator, &passphrase, t);
+        }
+    } else if config.backup {
+        let passphrase = config.generator.generate_passphrase();
+        println!("{}", passphrase.password);
+        print_backup(&config.generator, &passphrase, t);
     } else {
-        // 默认生成一个密码
-        println!("{}", generate());
+        println!("{}", config.generator.generate());
+    }
+    
+    if config.show_entropy && config.format == Format::Plain {
+        let bits = config.generator.entropy_bits();
+        let unweighted = config.generator.clone().all_tiers().entropy_bits();
+        if unweighted - bits > 0.05 {
+            println!("{}", fill(t.entropy_tiers, &[&format!("{:.1}", bits), &format!("{:.1}", unweighted)]));
+        } else {
+            println!("{}", fill(t.entropy, &[&format!("{:.1}", bits)]));
+        }
     }
 }
@@ -0,0 +1,694 @@
+//! Message catalogs of the command line tool
+//!
+//! Templates use `{}` placeholders, filled in order by [`fill`].
+
+use std::env;
+use std::fmt;
+
+/// Language of the command line tool's messages
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lang {
+    ZhCn,
+    ZhTw,
+    En,
+}
+
+impl Lang {
+    /// Parse a language tag such as "zh-CN", "zh_TW.UTF-8", "zh-Hant" or
+    /// "en_US"
+    pub fn parse(tag: &str) -> Option<Lang> {
+        let tag = tag.split(['.', '@']).next().unwrap_or_default().replace('_', "-").to_lowercase();
+        let mut subtags = tag.split('-');
+        match subtags.next()? {
+            "zh" if subtags.any(|subtag| matches!(subtag, "tw" | "hk" | "mo" | "hant")) => Some(Lang::ZhTw),
+            "zh" => Some(Lang::ZhCn),
+            "en" => Some(Lang::En),
+            _ => None,
+        }
+    }
+
+    /// The language of `LC_ALL`, `LC_MESSAGES` or `LANG`, the first one set
+    ///
+    /// Chinese is the default when none is set or the locale is "C" or
+    /// "POSIX"; any other language falls back to English.
+    pub fn from_env() -> Lang {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty());
+        match locale {
+            None => Lang::ZhCn,
+            Some(locale) if locale == "C" || locale == "POSIX" || locale.starts_with("C.") => Lang::ZhCn,
+            Some(locale) => Lang::parse(&locale).unwrap_or(Lang::En),
+        }
+    }
+
+    pub fn messages(self) -> &'static Messages {
+        match self {
+            Lang::ZhCn => &ZH_CN,
+            Lang::ZhTw => &ZH_TW,
+            Lang::En => &EN,
+        }
+    }
+}
+
+/// Fill the `{}` placeholders of a template in order
+pub fn fill(template: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(i) = rest.find("{}") {
+        out.push_str(&rest[..i]);
+        if let Some(arg) = args.next() {
+            out.push_str(&arg.to_string());
+        }
+        rest = &rest[i + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Every message the command line tool prints
+// The `extract` messages are unused without the `import` feature
+#[cfg_attr(not(feature = "import"), allow(dead_code))]
+pub struct Messages {
+    pub help: &'static str,
+    pub see_help: &'static str,
+
+    // Argument errors
+    pub takes_no_value: &'static str,
+    pub needs_value: &'static str,
+    pub invalid_value: &'static str,
+    pub unknown_option: &'static str,
+    pub unexpected_argument: &'static str,
+    pub expect_count: &'static str,
+    pub expect_number: &'static str,
+    pub expect_format: &'static str,
+    pub expect_modes: &'static str,
+    pub expect_sources: &'static str,
+    pub expect_length: &'static str,
+    pub expect_union: &'static str,
+    pub expect_weighted: &'static str,
+    pub expect_tier: &'static str,
+    pub expect_tier_weights: &'static str,
+    pub expect_lang: &'static str,
+    pub expect_provision_format: &'static str,
+    pub expect_min_entropy: &'static str,
+    pub expect_seconds: &'static str,
+
+    // File errors
+    pub cannot_read: &'static str,
+    pub cannot_read_corpus: &'static str,
+    pub cannot_read_users: &'static str,
+    pub cannot_parse: &'static str,
+    pub cannot_write: &'static str,
+
+    // Configuration file
+    pub cannot_read_config: &'static str,
+    pub no_config_location: &'static str,
+    pub config_invalid: &'static str,
+    pub unknown_profile: &'static str,
+    pub in_profile: &'static str,
+
+    // Generation
+    pub password: &'static str,
+    pub source: &'static str,
+    pub origin: &'static str,
+    pub origin_only: &'static str,
+    pub rendering: &'static str,
+    pub mode_chinese: &'static str,
+    pub mode_full: &'static str,
+    pub mode_init: &'static str,
+    pub mode_zhuyin: &'static str,
+    pub entropy: &'static str,
+    pub entropy_tiers: &'static str,
+    pub batch_failed: &'static str,
+    pub batch_too_many: &'static str,
+    pub batch_exhausted: &'static str,
+    pub invalid_separator: &'static str,
+    pub invalid_length: &'static str,
+    pub invalid_weights: &'static str,
+    pub empty_corpus: &'static str,
+    pub no_candidates: &'static str,
+    pub no_distinct_back: &'static str,
+    pub unknown_entry: &'static str,
+    pub insufficient_entropy: &'static str,
+    pub invalid_backup: &'static str,
+    pub backup_mismatch: &'static str,
+
+    // Clipboard
+    pub copy_single: &'static str,
+    pub copied: &'static str,
+    pub copied_until: &'static str,
+    pub copy_failed: &'static str,
+    pub copy_not_cleared: &'static str,
+
+    // Picker
+    pub pick_help: &'static str,
+    pub pick_terminal: &'static str,
+    pub pick_format: &'static str,
+
+    // Backup codes
+    pub backup_code: &'static str,
+    pub backup_words: &'static str,
+    pub backup_single: &'static str,
+
+    // Subcommands
+    pub validate_usage: &'static str,
+    pub validate_rejected: &'static str,
+    pub validate_duplicate: &'static str,
+    pub validate_collision: &'static str,
+    pub validate_summary: &'static str,
+    pub list_separator: &'static str,
+    pub extract_usage: &'static str,
+    pub extracted_lines: &'static str,
+    pub extracted_words: &'static str,
+    pub provision_usage: &'static str,
+    pub provision_failed: &'static str,
+    pub provisioned: &'static str,
+    pub duplicate_user: &'static str,
+    pub recover_usage: &'static str,
+}
+
+pub static ZH_CN: Messages = Messages {
+    help: "\
+诗词密码生成器
+用法: poetry-pass [选项]
+选项:
+  -i, --initials       使用拼音首字母
+  --full-pinyin        使用全拼（默认）
+  -c, --chinese        使用中文原文
+  --zhuyin             使用注音符号（含声调）
+  -d, --dual           双模式：前全拼后首字母
+  --dual-reverse       双模式：前首字母后全拼
+  --modes FRONT,BACK   前后两部分分别使用 full、init、chinese 或 zhuyin，如 --modes chinese,init
+  -r, --random-caps    随机大写至少一个字母
+  --no-random-caps     不随机大写（默认）
+  --separator SEP      分隔符（默认 -），--separator= 表示不分隔
+  --no-number          不添加数字
+  --number             添加数字（默认）
+  -n, --count N        生成N个互不相同的密码
+  --distinct-lines     与 -n 一起使用：每句诗词或词语最多出现在一个密码中
+  -s, --source         显示密码来源
+  -e, --entropy        显示密码强度估计（熵，不含大小写）
+  --pick               在全屏界面中从一页候选密码中挑选，可更换前后部分、切换模式；-n 设置每页个数
+  --copy               将密码复制到剪贴板而不显示，只显示来源
+  --copy-timeout SECS  同 --copy，SECS 秒后清除剪贴板（默认 45，0 表示不清除）
+  --backup             同时显示备份码和备份词，可用 recover 子命令还原密码
+  --format FORMAT      输出格式：plain（默认）、json（每行一个 JSON 对象）或 tsv
+  --json               等同于 --format json
+  --poetry             诗句-诗句组合
+  --words              词语-词语组合
+  --word-poem          词语-诗句组合
+  --poem-word          诗句-词语组合
+  --word-word          词语-词语组合
+  --poem-poem          诗句-诗句组合
+  --chengyu            成语-成语组合
+  --song-ci            宋词-宋词组合（每句 3-11 字）
+  --yuan-qu            元曲-元曲组合（每句 3-11 字）
+  --lunyu              论语名句组合
+  --daodejing          道德经名句组合
+  --shijing            诗经诗句组合
+  --guwen              古文名句组合（多出自古文观止）
+  --sources FRONT,BACK 前后两部分分别使用的来源，如 --sources chengyu,lunyu
+  --length MIN-MAX     只使用 MIN 到 MAX 字的句子，如 --length 4-7
+  --union A,B,...      从多个来源的所有句子中等概率抽取，如 --union poetry,words,chengyu
+  --weighted A=W,...   按权重选择来源再抽取，如 --weighted poetry=3,chengyu=1
+                       来源: poetry words chengyu song-ci yuan-qu lunyu daodejing shijing guwen
+  --tier TIER          只使用该熟悉度及更熟悉的句子：famous（名句）、common（常见）、obscure（全部）
+  --tier-weights F,C,O 按熟悉度加权抽取，如 4,2,1 偏向名句而不排除生僻句
+  --all-tiers          取消熟悉度限制和权重（默认）
+  --allow-repeats      允许前后两部分相同或相近（默认不允许，如 yw-yw）
+  --distinct           不允许前后两部分相同或相近（默认）
+  --min-entropy BITS   熵低于 BITS 位时报错而不生成密码
+  --meaning            来源中显示成语释义
+  --no-meaning         来源中不显示成语释义（默认）
+  --corpus PATH        使用自定义词库（文本文件、chinese-poetry JSON 或目录）
+  --profile NAME       使用配置文件中的 profile，命令行选项优先
+  --config PATH        配置文件（默认 ~/.config/poetry-pass/config.toml）
+  --no-profile         不使用配置文件中的 profile
+  --lang LANG          界面语言：zh-CN、zh-TW 或 en（默认取自 LANG 环境变量）
+  -h, --help           显示帮助
+
+带参数的选项也可写成 --length=4-7；短选项可以合并，如 -dr、-dn5。未知选项会报错。
+
+子命令:
+  validate FILE [-o OUT] [--collisions]
+                       检查词库：去重、规范化、拒绝无法转拼音的字符
+                       -o 写出清理后的词库，--collisions 列出首字母相同的行
+  extract poetry|words INPUT [-o OUT] [--min N] [--max N] [--level N]
+                       从诗词 JSON（paragraphs）或 HSK 词表 JSON 提取词库
+                       默认诗句 5-7 字，词语 2-4 字、HSK 1-3 级
+  provision USERS|- [-o OUT] [--format csv|json] [生成选项]
+                       为用户列表（文件或标准输入，每行一个）批量生成互不相同的密码
+                       输出 username,password,source,entropy；可加 -d、--chengyu 等生成选项
+  recover CODE [生成选项]
+                       从 --backup 显示的备份码或备份词还原密码
+                       来源、模式和分隔符须与生成时相同；可加 -s 或 --copy
+
+示例:
+  poetry-pass -d       # 生成如 huaduo-hlzdc 格式
+  poetry-pass --dual-reverse  # 生成如 hd-huaduolizidanci 格式
+  poetry-pass --word-poem     # 生成如 huaduo-yuelangxingxi1234 格式
+  poetry-pass --poem-word     # 生成如 yuelangxingxi-huaduo1234 格式
+  poetry-pass --word-poem -s  # 显示词语-诗句密码及来源
+  poetry-pass --song-ci --length 4-6 -s  # 4-6 字的宋词句子及出处
+  poetry-pass --poem-poem --tier famous -e  # 只用名句，并显示熵的损失
+  poetry-pass --poetry --separator=_ --no-number -c  # 如 明月松间照_清泉石上流
+  poetry-pass -d -n 5 --json  # 每行一个 JSON 记录，含来源、各部分和熵
+  poetry-pass --profile work-vpn -c  # 使用 work-vpn 的设置，但输出中文
+",
+    see_help: "使用 --help 查看所有选项",
+
+    takes_no_value: "{} 不接受参数",
+    needs_value: "{} 需要一个参数",
+    invalid_value: "{} 的参数必须是{}: {}",
+    unknown_option: "未知选项: {}",
+    unexpected_argument: "多余的参数: {}",
+    expect_count: "正整数",
+    expect_number: "数字",
+    expect_format: " plain、json 或 tsv",
+    expect_modes: "两种模式，如 full,init",
+    expect_sources: "前后两个来源，如 words,poetry",
+    expect_length: " MIN-MAX 或 N",
+    expect_union: "逗号分隔的来源，如 poetry,words",
+    expect_weighted: " 来源=权重，如 poetry=3,chengyu=1",
+    expect_tier: " famous、common 或 obscure",
+    expect_tier_weights: "三个非负数，如 4,2,1",
+    expect_lang: " zh-CN、zh-TW 或 en",
+    expect_provision_format: " csv 或 json",
+    expect_min_entropy: "非负数（位）",
+    expect_seconds: "非负整数（秒）",
+
+    cannot_read: "无法读取 {}: {}",
+    cannot_read_corpus: "无法读取词库 {}: {}",
+    cannot_read_users: "无法读取用户列表 {}: {}",
+    cannot_parse: "无法解析 {}: {}",
+    cannot_write: "无法写入 {}: {}",
+
+    cannot_read_config: "无法读取配置文件 {}: {}",
+    no_config_location: "找不到配置文件的位置：XDG_CONFIG_HOME 和 HOME 均未设置，请用 --config 指定",
+    config_invalid: "{}:{}: {}",
+    unknown_profile: "配置文件 {} 中没有 profile: {}",
+    in_profile: "profile {}: {}",
+
+    password: "密码: {}",
+    source: "来源: {}",
+    origin: "出处: {} —— {}",
+    origin_only: "出处: {}",
+    rendering: "  {}: {}",
+    mode_chinese: "中文",
+    mode_full: "全拼",
+    mode_init: "首字母",
+    mode_zhuyin: "注音",
+    entropy: "熵: {} 位",
+    entropy_tiers: "熵: {} 位（不限熟悉度时 {} 位）",
+    batch_failed: "无法生成 {} 个不同的密码: {}",
+    batch_too_many: "要求 {} 个，但当前设置最多只能生成 {} 个",
+    batch_exhausted: "要求 {} 个，只生成了 {} 个",
+    invalid_separator: "分隔符不能包含字母、数字、空白或控制字符: \"{}\"",
+    invalid_length: "长度下限 {} 大于上限 {}",
+    invalid_weights: "权重必须是非负数且不能全为 0",
+    empty_corpus: "{} 中没有可用的句子",
+    no_candidates: "{} 中没有符合长度和熟悉度限制的句子",
+    no_distinct_back: "{} 中没有与前半部分 \"{}\" 不同的句子",
+    unknown_entry: "{} 中没有第 {} 条",
+    insufficient_entropy: "熵 {} 位低于要求的 {} 位",
+    invalid_backup: "备份码无效，请检查是否抄错",
+    backup_mismatch: "备份码与当前设置不符，请使用生成时的来源、模式和分隔符",
+    copy_single: "--copy 只能用于生成单个纯文本密码，不能与 -n 或 --format 一起使用",
+    copied: "密码已复制到剪贴板",
+    copied_until: "密码已复制到剪贴板，{} 秒后清除",
+    copy_failed: "无法复制到剪贴板: {}",
+    copy_not_cleared: "密码已复制到剪贴板，但无法自动清除: {}",
+    pick_help: "↑/↓ 或编号移动，回车选用，f/b 更换前/后半部分，n/c 更换数字/大写字母，m/M 切换前/后半部分的模式，空格换一页，q 退出",
+    pick_terminal: "--pick 需要在终端中运行",
+    pick_format: "--pick 只输出一个密码，不能与 --format 一起使用",
+    backup_code: "备份码: {}",
+    backup_words: "备份词: {}",
+    backup_single: "--backup 只能用于生成单个纯文本密码，不能与 -n 或 --format 一起使用",
+
+    validate_usage: "用法: poetry-pass validate FILE [-o OUT] [--collisions]",
+    validate_rejected: "{}:{}: 拒绝 \"{}\"（无法转换拼音: '{}'）",
+    validate_duplicate: "{}: 重复 \"{}\"",
+    validate_collision: "首字母冲突 {}: {}",
+    validate_summary: "有效 {} 行，重复 {} 行，拒绝 {} 行，首字母冲突 {} 组",
+    list_separator: "、",
+    extract_usage: "用法: poetry-pass extract poetry|words INPUT [-o OUT] [--min N] [--max N] [--level N]",
+    extracted_lines: "提取了 {} 条诗句",
+    extracted_words: "提取了 {} 个词语",
+    provision_usage: "用法: poetry-pass provision USERS|- [-o OUT] [--format csv|json] [生成选项]",
+    provision_failed: "无法为 {} 个用户生成不同的密码: {}",
+    provisioned: "为 {} 个用户生成了密码",
+    duplicate_user: "用户列表第 {} 行: 用户 {} 重复出现",
+    recover_usage: "用法: poetry-pass recover CODE [生成选项]",
+};
+
+pub static ZH_TW: Messages = Messages {
+    help: "\
+詩詞密碼產生器
+用法: poetry-pass [選項]
+選項:
+  -i, --initials       使用拼音首字母
+  --full-pinyin        使用全拼（預設）
+  -c, --chinese        使用中文原文
+  --zhuyin             使用注音符號（含聲調）
+  -d, --dual           雙模式：前全拼後首字母
+  --dual-reverse       雙模式：前首字母後全拼
+  --modes FRONT,BACK   前後兩部分分別使用 full、init、chinese 或 zhuyin，如 --modes chinese,init
+  -r, --random-caps    隨機大寫至少一個字母
+  --no-random-caps     不隨機大寫（預設）
+  --separator SEP      分隔符號（預設 -），--separator= 表示不分隔
+  --no-number          不加數字
+  --number             加上數字（預設）
+  -n, --count N        產生N個互不相同的密碼
+  --distinct-lines     與 -n 一起使用：每句詩詞或詞語最多出現在一個密碼中
+  -s, --source         顯示密碼來源
+  -e, --entropy        顯示密碼強度估計（熵，不含大小寫）
+  --pick               在全螢幕介面中從一頁候選密碼中挑選，可更換前後部分、切換模式；-n 設定每頁個數
+  --copy               將密碼複製到剪貼簿而不顯示，只顯示來源
+  --copy-timeout SECS  同 --copy，SECS 秒後清除剪貼簿（預設 45，0 表示不清除）
+  --backup             同時顯示備份碼和備份詞，可用 recover 子命令還原密碼
+  --format FORMAT      輸出格式：plain（預設）、json（每行一個 JSON 物件）或 tsv
+  --json               等同於 --format json
+  --poetry             詩句-詩句組合
+  --words              詞語-詞語組合
+  --word-poem          詞語-詩句組合
+  --poem-word          詩句-詞語組合
+  --word-word          詞語-詞語組合
+  --poem-poem          詩句-詩句組合
+  --chengyu            成語-成語組合
+  --song-ci            宋詞-宋詞組合（每句 3-11 字）
+  --yuan-qu            元曲-元曲組合（每句 3-11 字）
+  --lunyu              論語名句組合
+  --daodejing          道德經名句組合
+  --shijing            詩經詩句組合
+  --guwen              古文名句組合（多出自古文觀止）
+  --sources FRONT,BACK 前後兩部分分別使用的來源，如 --sources chengyu,lunyu
+  --length MIN-MAX     只使用 MIN 到 MAX 字的句子，如 --length 4-7
+  --union A,B,...      從多個來源的所有句子中等機率抽取，如 --union poetry,words,chengyu
+  --weighted A=W,...   依權重選擇來源再抽取，如 --weighted poetry=3,chengyu=1
+                       來源: poetry words chengyu song-ci yuan-qu lunyu daodejing shijing guwen
+  --tier TIER          只使用該熟悉度及更熟悉的句子：famous（名句）、common（常見）、obscure（全部）
+  --tier-weights F,C,O 依熟悉度加權抽取，如 4,2,1 偏向名句而不排除冷僻句
+  --all-tiers          取消熟悉度限制和權重（預設）
+  --allow-repeats      允許前後兩部分相同或相近（預設不允許，如 yw-yw）
+  --distinct           不允許前後兩部分相同或相近（預設）
+  --min-entropy BITS   熵低於 BITS 位時報錯而不產生密碼
+  --meaning            來源中顯示成語釋義
+  --no-meaning         來源中不顯示成語釋義（預設）
+  --corpus PATH        使用自訂詞庫（文字檔、chinese-poetry JSON 或目錄）
+  --profile NAME       使用設定檔中的 profile，命令列選項優先
+  --config PATH        設定檔（預設 ~/.config/poetry-pass/config.toml）
+  --no-profile         不使用設定檔中的 profile
+  --lang LANG          介面語言：zh-CN、zh-TW 或 en（預設取自 LANG 環境變數）
+  -h, --help           顯示說明
+
+帶參數的選項也可寫成 --length=4-7；短選項可以合併，如 -dr、-dn5。未知選項會報錯。
+
+子命令:
+  validate FILE [-o OUT] [--collisions]
+                       檢查詞庫：去重、正規化、拒絕無法轉拼音的字元
+                       -o 寫出清理後的詞庫，--collisions 列出首字母相同的行
+  extract poetry|words INPUT [-o OUT] [--min N] [--max N] [--level N]
+                       從詩詞 JSON（paragraphs）或 HSK 詞表 JSON 擷取詞庫
+                       預設詩句 5-7 字，詞語 2-4 字、HSK 1-3 級
+  provision USERS|- [-o OUT] [--format csv|json] [產生選項]
+                       為使用者清單（檔案或標準輸入，每行一個）批次產生互不相同的密碼
+                       輸出 username,password,source,entropy；可加 -d、--chengyu 等產生選項
+  recover CODE [產生選項]
+                       從 --backup 顯示的備份碼或備份詞還原密碼
+                       來源、模式和分隔符須與產生時相同；可加 -s 或 --copy
+
+範例:
+  poetry-pass -d       # 產生如 huaduo-hlzdc 格式
+  poetry-pass --dual-reverse  # 產生如 hd-huaduolizidanci 格式
+  poetry-pass --word-poem     # 產生如 huaduo-yuelangxingxi1234 格式
+  poetry-pass --poem-word     # 產生如 yuelangxingxi-huaduo1234 格式
+  poetry-pass --word-poem -s  # 顯示詞語-詩句密碼及來源
+  poetry-pass --song-ci --length 4-6 -s  # 4-6 字的宋詞句子及出處
+  poetry-pass --poem-poem --tier famous -e  # 只用名句，並顯示熵的損失
+  poetry-pass --poetry --separator=_ --no-number -c  # 如 明月松间照_清泉石上流
+  poetry-pass -d -n 5 --json  # 每行一筆 JSON 紀錄，含來源、各部分和熵
+  poetry-pass --profile work-vpn -c  # 使用 work-vpn 的設定，但輸出中文
+",
+    see_help: "使用 --help 查看所有選項",
+
+    takes_no_value: "{} 不接受參數",
+    needs_value: "{} 需要一個參數",
+    invalid_value: "{} 的參數必須是{}: {}",
+    unknown_option: "未知選項: {}",
+    unexpected_argument: "多餘的參數: {}",
+    expect_count: "正整數",
+    expect_number: "數字",
+    expect_format: " plain、json 或 tsv",
+    expect_modes: "兩種模式，如 full,init",
+    expect_sources: "前後兩個來源，如 words,poetry",
+    expect_length: " MIN-MAX 或 N",
+    expect_union: "逗號分隔的來源，如 poetry,words",
+    expect_weighted: " 來源=權重，如 poetry=3,chengyu=1",
+    expect_tier: " famous、common 或 obscure",
+    expect_tier_weights: "三個非負數，如 4,2,1",
+    expect_lang: " zh-CN、zh-TW 或 en",
+    expect_provision_format: " csv 或 json",
+    expect_min_entropy: "非負數（位）",
+    expect_seconds: "非負整數（秒）",
+
+    cannot_read: "無法讀取 {}: {}",
+    cannot_read_corpus: "無法讀取詞庫 {}: {}",
+    cannot_read_users: "無法讀取使用者清單 {}: {}",
+    cannot_parse: "無法解析 {}: {}",
+    cannot_write: "無法寫入 {}: {}",
+
+    cannot_read_config: "無法讀取設定檔 {}: {}",
+    no_config_location: "找不到設定檔的位置：XDG_CONFIG_HOME 和 HOME 皆未設定，請用 --config 指定",
+    config_invalid: "{}:{}: {}",
+    unknown_profile: "設定檔 {} 中沒有 profile: {}",
+    in_profile: "profile {}: {}",
+
+    password: "密碼: {}",
+    source: "來源: {}",
+    origin: "出處: {} —— {}",
+    origin_only: "出處: {}",
+    rendering: "  {}: {}",
+    mode_chinese: "中文",
+    mode_full: "全拼",
+    mode_init: "首字母",
+    mode_zhuyin: "注音",
+    entropy: "熵: {} 位元",
+    entropy_tiers: "熵: {} 位元（不限熟悉度時 {} 位元）",
+    batch_failed: "無法產生 {} 個不同的密碼: {}",
+    batch_too_many: "要求 {} 個，但目前設定最多只能產生 {} 個",
+    batch_exhausted: "要求 {} 個，只產生了 {} 個",
+    invalid_separator: "分隔符不能包含字母、數字、空白或控制字元: \"{}\"",
+    invalid_length: "長度下限 {} 大於上限 {}",
+    invalid_weights: "權重必須是非負數且不能全為 0",
+    empty_corpus: "{} 中沒有可用的句子",
+    no_candidates: "{} 中沒有符合長度和熟悉度限制的句子",
+    no_distinct_back: "{} 中沒有與前半部分 \"{}\" 不同的句子",
+    unknown_entry: "{} 中沒有第 {} 條",
+    insufficient_entropy: "熵 {} 位低於要求的 {} 位",
+    invalid_backup: "備份碼無效，請檢查是否抄錯",
+    backup_mismatch: "備份碼與目前設定不符，請使用產生時的來源、模式和分隔符",
+    copy_single: "--copy 只能用於產生單個純文字密碼，不能與 -n 或 --format 一起使用",
+    copied: "密碼已複製到剪貼簿",
+    copied_until: "密碼已複製到剪貼簿，{} 秒後清除",
+    copy_failed: "無法複製到剪貼簿: {}",
+    copy_not_cleared: "密碼已複製到剪貼簿，但無法自動清除: {}",
+    pick_help: "↑/↓ 或編號移動，Enter 選用，f/b 更換前/後半部分，n/c 更換數字/大寫字母，m/M 切換前/後半部分的模式，空白鍵換一頁，q 離開",
+    pick_terminal: "--pick 需要在終端機中執行",
+    pick_format: "--pick 只輸出一個密碼，不能與 --format 一起使用",
+    backup_code: "備份碼: {}",
+    backup_words: "備份詞: {}",
+    backup_single: "--backup 只能用於產生單一純文字密碼，不能與 -n 或 --format 一起使用",
+
+    validate_usage: "用法: poetry-pass validate FILE [-o OUT] [--collisions]",
+    validate_rejected: "{}:{}: 拒絕 \"{}\"（無法轉換拼音: '{}'）",
+    validate_duplicate: "{}: 重複 \"{}\"",
+    validate_collision: "首字母衝突 {}: {}",
+    validate_summary: "有效 {} 行，重複 {} 行，拒絕 {} 行，首字母衝突 {} 組",
+    list_separator: "、",
+    extract_usage: "用法: poetry-pass extract poetry|words INPUT [-o OUT] [--min N] [--max N] [--level N]",
+    extracted_lines: "擷取了 {} 條詩句",
+    extracted_words: "擷取了 {} 個詞語",
+    provision_usage: "用法: poetry-pass provision USERS|- [-o OUT] [--format csv|json] [產生選項]",
+    provision_failed: "無法為 {} 個使用者產生不同的密碼: {}",
+    provisioned: "為 {} 個使用者產生了密碼",
+    duplicate_user: "使用者清單第 {} 行: 使用者 {} 重複出現",
+    recover_usage: "用法: poetry-pass recover CODE [產生選項]",
+};
+
+pub static EN: Messages = Messages {
+    help: "\
+Poetry password generator
+Usage: poetry-pass [OPTIONS]
+Options:
+  -i, --initials       Use pinyin initials
+  --full-pinyin        Use full pinyin (default)
+  -c, --chinese        Keep the original Chinese
+  --zhuyin             Use Zhuyin (bopomofo) with tone marks
+  -d, --dual           Dual mode: full pinyin front, initials back
+  --dual-reverse       Dual mode: initials front, full pinyin back
+  --modes FRONT,BACK   Mode of each part: full, init, chinese or zhuyin, e.g. --modes chinese,init
+  -r, --random-caps    Capitalize at least one random letter
+  --no-random-caps     Don't capitalize (default)
+  --separator SEP      Separator (default -); --separator= for none
+  --no-number          Don't add a number
+  --number             Add a number (default)
+  -n, --count N        Generate N different passwords
+  --distinct-lines     With -n: use every poetry line or word in at most one password
+  -s, --source         Show where the password comes from
+  -e, --entropy        Show the estimated strength (entropy, not counting capitalization)
+  --pick               Choose from a full-screen page of candidates, rerolling parts and switching modes; -n sets the page size
+  --copy               Copy the password to the clipboard instead of printing it; only the source is shown
+  --copy-timeout SECS  Like --copy, clearing the clipboard after SECS seconds (default 45, 0 never)
+  --backup             Also show a backup code and backup words that the recover subcommand turns back into the password
+  --format FORMAT      Output format: plain (default), json (one JSON object per line) or tsv
+  --json               Same as --format json
+  --poetry             Poetry line + poetry line
+  --words              Word + word
+  --word-poem          Word + poetry line
+  --poem-word          Poetry line + word
+  --word-word          Word + word
+  --poem-poem          Poetry line + poetry line
+  --chengyu            Idiom (成语) + idiom
+  --song-ci            宋词 lines (3-11 characters each)
+  --yuan-qu            元曲 lines (3-11 characters each)
+  --lunyu              论语 (Analects) quotations
+  --daodejing          道德经 (Tao Te Ching) quotations
+  --shijing            诗经 (Book of Songs) lines
+  --guwen              古文 quotations (mostly from 古文观止)
+  --sources FRONT,BACK Source of each part, e.g. --sources chengyu,lunyu
+  --length MIN-MAX     Only use lines of MIN to MAX characters, e.g. --length 4-7
+  --union A,B,...      Draw uniformly from all lines of the sources, e.g. --union poetry,words,chengyu
+  --weighted A=W,...   Pick a source by weight, then a line of it, e.g. --weighted poetry=3,chengyu=1
+                       Sources: poetry words chengyu song-ci yuan-qu lunyu daodejing shijing guwen
+  --tier TIER          Only use lines this well known or better: famous, common, obscure (all)
+  --tier-weights F,C,O Weight lines by familiarity, e.g. 4,2,1 favours famous lines without excluding obscure ones
+  --all-tiers          Drop familiarity limits and weights (default)
+  --allow-repeats      Allow the two parts to be the same or alike (not allowed by default, e.g. yw-yw)
+  --distinct           Keep the two parts distinct (default)
+  --min-entropy BITS   Fail instead of generating if the entropy is below BITS
+  --meaning            Show idiom meanings in the source
+  --no-meaning         Don't show idiom meanings (default)
+  --corpus PATH        Use a custom corpus (text file, chinese-poetry JSON or directory)
+  --profile NAME       Use a profile of the configuration file; command line options take precedence
+  --config PATH        Configuration file (default ~/.config/poetry-pass/config.toml)
+  --no-profile         Ignore the profiles of the configuration file
+  --lang LANG          Interface language: zh-CN, zh-TW or en (default from LANG)
+  -h, --help           Show this help
+
+Options with a value can also be written as --length=4-7; short flags combine, as in -dr or -dn5.
+Unknown options are reported as errors.
+
+Subcommands:
+  validate FILE [-o OUT] [--collisions]
+                       Check a corpus: remove duplicates, normalize, reject characters without pinyin
+                       -o writes the cleaned corpus, --collisions lists lines with the same initials
+  extract poetry|words INPUT [-o OUT] [--min N] [--max N] [--level N]
+                       Extract a corpus from poetry JSON (paragraphs) or HSK vocabulary JSON
+                       Defaults: 5-7 character lines; 2-4 character words of HSK levels 1-3
+  provision USERS|- [-o OUT] [--format csv|json] [GENERATOR OPTIONS]
+                       Generate different passwords for a list of users (file or stdin, one per line)
+                       Writes username,password,source,entropy; generator options such as -d or --chengyu apply
+  recover CODE [GENERATOR OPTIONS]
+                       Turn a backup code or backup words shown by --backup back into the password
+                       Sources, modes and separator must be those it was generated with; -s and --copy apply
+
+Examples:
+  poetry-pass -d       # e.g. huaduo-hlzdc
+  poetry-pass --dual-reverse  # e.g. hd-huaduolizidanci
+  poetry-pass --word-poem     # e.g. huaduo-yuelangxingxi1234
+  poetry-pass --poem-word     # e.g. yuelangxingxi-huaduo1234
+  poetry-pass --word-poem -s  # word-poetry password with its source
+  poetry-pass --song-ci --length 4-6 -s  # 4-6 character 宋词 lines with author and title
+  poetry-pass --poem-poem --tier famous -e  # famous lines only, showing the entropy cost
+  poetry-pass --poetry --separator=_ --no-number -c  # e.g. 明月松间照_清泉石上流
+  poetry-pass -d -n 5 --json  # one JSON record per line with source, parts and entropy
+  poetry-pass --profile work-vpn -c  # the work-vpn settings, but in Chinese
+",
+    see_help: "Run with --help to see all options",
+
+    takes_no_value: "{} does not take a value",
+    needs_value: "{} needs a value",
+    invalid_value: "{} must be {}: {}",
+    unknown_option: "Unknown option: {}",
+    unexpected_argument: "Unexpected argument: {}",
+    expect_count: "a positive integer",
+    expect_number: "a number",
+    expect_format: "plain, json or tsv",
+    expect_modes: "two modes, e.g. full,init",
+    expect_sources: "a front and a back source, e.g. words,poetry",
+    expect_length: "MIN-MAX or N",
+    expect_union: "comma-separated sources, e.g. poetry,words",
+    expect_weighted: "source=weight pairs, e.g. poetry=3,chengyu=1",
+    expect_tier: "famous, common or obscure",
+    expect_tier_weights: "three non-negative numbers, e.g. 4,2,1",
+    expect_lang: "zh-CN, zh-TW or en",
+    expect_provision_format: "csv or json",
+    expect_min_entropy: "a non-negative number of bits",
+    expect_seconds: "a whole number of seconds",
+
+    cannot_read: "Cannot read {}: {}",
+    cannot_read_corpus: "Cannot read corpus {}: {}",
+    cannot_read_users: "Cannot read user list {}: {}",
+    cannot_parse: "Cannot parse {}: {}",
+    cannot_write: "Cannot write {}: {}",
+
+    cannot_read_config: "Cannot read configuration file {}: {}",
+    no_config_location: "No configuration file location: neither XDG_CONFIG_HOME nor HOME is set; use --config",
+    config_invalid: "{}:{}: {}",
+    unknown_profile: "No profile in {} named {}",
+    in_profile: "Profile {}: {}",
+
+    password: "Password: {}",
+    source: "Source: {}",
+    origin: "From: {} — {}",
+    origin_only: "From: {}",
+    rendering: "  {}: {}",
+    mode_chinese: "Chinese",
+    mode_full: "Pinyin",
+    mode_init: "Initials",
+    mode_zhuyin: "Zhuyin",
+    entropy: "Entropy: {} bits",
+    entropy_tiers: "Entropy: {} bits ({} bits without familiarity limits)",
+    batch_failed: "Cannot generate {} different passwords: {}",
+    batch_too_many: "{} requested but the settings allow at most {}",
+    batch_exhausted: "{} requested but only {} could be generated",
+    invalid_separator: "The separator must not contain letters, digits, whitespace or control characters: \"{}\"",
+    invalid_length: "The minimum length {} exceeds the maximum {}",
+    invalid_weights: "Weights must be non-negative and not all zero",
+    empty_corpus: "No entries in {}",
+    no_candidates: "No entries of {} within the length and tier limits",
+    no_distinct_back: "No entry of {} differs from the front part \"{}\"",
+    unknown_entry: "{} has no entry {}",
+    insufficient_entropy: "Entropy of {} bits is below the required {} bits",
+    invalid_backup: "Invalid backup code, check it for typos",
+    backup_mismatch: "The backup code does not match these settings; use the sources, modes and separator it was generated with",
+    copy_single: "--copy only works for a single plain password, not with -n or --format",
+    copied: "Password copied to the clipboard",
+    copied_until: "Password copied to the clipboard, it will be cleared in {} seconds",
+    copy_failed: "Cannot copy to the clipboard: {}",
+    copy_not_cleared: "Password copied to the clipboard, but it cannot be cleared automatically: {}",
+    pick_help: "Up/Down or a number to move, Enter to accept, f/b for a new front/back part, n/c for a new number/capitals, m/M to switch the front/back mode, Space for a new page, q to quit",
+    pick_terminal: "--pick needs a terminal",
+    pick_format: "--pick prints a single password and cannot be combined with --format",
+    backup_code: "Backup code: {}",
+    backup_words: "Backup words: {}",
+    backup_single: "--backup works with a single plain password and cannot be combined with -n or --format",
+
+    validate_usage: "Usage: poetry-pass validate FILE [-o OUT] [--collisions]",
+    validate_rejected: "{}:{}: rejected \"{}\" (no pinyin for '{}')",
+    validate_duplicate: "{}: duplicate \"{}\"",
+    validate_collision: "Initials collision {}: {}",
+    validate_summary: "{} valid lines, {} duplicates, {} rejected, {} initials collisions",
+    list_separator: ", ",
+    extract_usage: "Usage: poetry-pass extract poetry|words INPUT [-o OUT] [--min N] [--max N] [--level N]",
+    extracted_lines: "Extracted {} poetry lines",
+    extracted_words: "Extracted {} words",
+    provision_usage: "Usage: poetry-pass provision USERS|- [-o OUT] [--format csv|json] [GENERATOR OPTIONS]",
+    provision_failed: "Cannot generate different passwords for {} users: {}",
+    provisioned: "Generated passwords for {} users",
+    duplicate_user: "User list line {}: user {} appears more than once",
+    recover_usage: "Usage: poetry-pass recover CODE [GENERATOR OPTIONS]",
+};
@@ -0,0 +1,101 @@
+use crate::corpus::Entry;
+use crate::transform::{transform_entry, Mode};
+use std::fmt;
+use std::ops::Range;
+
+/// One part of a password: the corpus entry it was drawn from and how it
+/// was rendered
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Segment {
+    pub entry: Entry,
+    pub mode: Mode,
+    /// The entry rendered in `mode`, before random capitalization
+    pub text: String,
+    /// Name of the corpus the entry was drawn from
+    pub corpus: String,
+    /// Index of the entry in that corpus, see
+    /// [`Corpus::entry`](crate::Corpus::entry)
+    pub index: usize,
+}
+
+/// A generated password together with everything it was built from
+///
+/// Displays as the password.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Passphrase {
+    pub segments: Vec<Segment>,
+    /// The number appended after the segments, if any
+    pub number: Option<u32>,
+    pub separator: String,
+    /// The password as generated, including random capitalization
+    pub password: String,
+    /// Positions of the characters of `password` that random
+    /// capitalization upper-cased, in order
+    pub capitals: Vec<usize>,
+    /// Estimated entropy of the generator settings, see
+    /// [`Generator::entropy_bits`](crate::Generator::entropy_bits)
+    pub entropy_bits: f64,
+}
+
+impl Passphrase {
+    /// The corpus entries of the segments
+    pub fn entries(&self) -> Vec<Entry> {
+        self.segments.iter().map(|segment| segment.entry.clone()).collect()
+    }
+
+    /// The source text of the segments joined with the separator, e.g.
+    /// "花朵-明月几时有"
+    pub fn source(&self) -> String {
+        self.segments.iter()
+            .map(|segment| segment.entry.text.as_str())
+            .collect::<Vec<_>>()
+            .join(&self.separator)
+    }
+
+    /// Positions of the characters of segment `index` in `password`
+    ///
+    /// # Panics
+    ///
+    /// If `index` is not a segment.
+    pub fn segment_range(&self, index: usize) -> Range<usize> {
+        let separator = self.separator.chars().count();
+        let start = self.segments[..index].iter().map(|segment| segment.text.chars().count() + separator).sum();
+        start..start + self.segments[index].text.chars().count()
+    }
+
+    /// The same segments and number rendered in `mode`, without random
+    /// capitalization, e.g. the Chinese or Zhuyin form of a pinyin password
+    pub fn render_in(&self, mode: &Mode) -> String {
+        let mut parts: Vec<String> = self.segments.iter().map(|segment| transform_entry(&segment.entry, mode)).collect();
+        parts.extend(self.number.map(|number| number.to_string()));
+        parts.join(&self.separator)
+    }
+
+    /// The corpus name and index of each segment
+    pub(crate) fn origins(&self) -> Vec<(String, usize)> {
+        self.segments.iter().map(|segment| (segment.corpus.clone(), segment.index)).collect()
+    }
+
+    /// The password without random capitalization
+    pub(crate) fn uncapitalized(&self) -> String {
+        let mut parts: Vec<String> = self.segments.iter().map(|segment| segment.text.clone()).collect();
+        parts.extend(self.number.map(|number| number.to_string()));
+        parts.join(&self.separator)
+    }
+
+    /// The modes of the segments, e.g. "pinyin_full" or
+    /// "pinyin_full/pinyin_init" when they differ
+    pub fn mode_name(&self) -> String {
+        let mut names: Vec<&str> = self.segments.iter().map(|segment| segment.mode.name()).collect();
+        names.dedup();
+        names.join("/")
+    }
+}
+
+impl fmt::Display for Passphrase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.password)
+    }
+}
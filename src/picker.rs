@@ -0,0 +1,254 @@
+//! Picker: a full-screen page of candidates to choose from
+//!
+//! The page is drawn on the alternate screen of the terminal behind standard
+//! error and redrawn in place after every key, so only the accepted password
+//! reaches standard output and `pw=$(poetry-pass --pick)` works. Keys move
+//! the selection, draw new parts of the selected candidate or a new page,
+//! and switch the mode of either part.
+
+use crate::messages::{fill, Messages};
+use crate::{batch_error, generator_error, origin, Config};
+use crossterm::cursor::{Hide, MoveTo, MoveToNextLine, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use poetry_pass::{Generator, Mode, Passphrase};
+use std::io::{self, IsTerminal, Write};
+
+/// Candidates on a page unless `-n` says otherwise
+pub const PAGE_SIZE: usize = 6;
+
+/// Modes in the order `m` and `M` step through them
+const MODES: [Mode; 4] = [Mode::PinyinFull, Mode::PinyinInit, Mode::Chinese, Mode::Zhuyin];
+
+/// Show pages of candidates until one is accepted, returning it with the
+/// generator in the mode it was drawn in; `None` if the user quits
+pub fn run(config: &Config, t: &Messages) -> Option<(Generator, Passphrase)> {
+    if !io::stderr().is_terminal() {
+        eprintln!("{}", t.pick_terminal);
+        return None;
+    }
+    let generator = config.generator.clone();
+    let page = new_page(&generator, config, t).map_err(|message| eprintln!("{}", message)).ok()?;
+    let mut picker = Picker { config, t, generator, page, selected: 0, status: None };
+    let mut screen = Screen::enter().map_err(|e| eprintln!("{}", e)).ok()?;
+
+    loop {
+        picker.draw(&mut screen.0).ok()?;
+        let key = match event::read().ok()? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => key,
+            // A resize or a key release: draw the page again
+            _ => continue,
+        };
+        picker.status = None;
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return None,
+            KeyCode::Char('q') | KeyCode::Esc => return None,
+            KeyCode::Enter => {
+                let passphrase = picker.page.swap_remove(picker.selected);
+                return Some((picker.generator, passphrase));
+            }
+            KeyCode::Up | KeyCode::Char('k') => picker.selected = picker.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => picker.selected = (picker.selected + 1).min(picker.page.len() - 1),
+            KeyCode::Char(digit @ '1'..='9') => {
+                let i = digit as usize - '1' as usize;
+                if i < picker.page.len() {
+                    picker.selected = i;
+                }
+            }
+            KeyCode::Char(' ') | KeyCode::Char('r') => match new_page(&picker.generator, config, t) {
+                Ok(page) => picker.show(page),
+                Err(message) => picker.status = Some(message),
+            },
+            KeyCode::Char(part @ ('f' | 'b')) => {
+                let selected = &picker.page[picker.selected];
+                match picker.generator.reroll_segment(selected, if part == 'f' { 0 } else { 1 }) {
+                    Ok(passphrase) => picker.page[picker.selected] = passphrase,
+                    Err(e) => picker.status = Some(generator_error(&e, t).message),
+                }
+            }
+            KeyCode::Char('n') => {
+                picker.page[picker.selected] = picker.generator.reroll_number(&picker.page[picker.selected]);
+            }
+            KeyCode::Char('c') => {
+                picker.page[picker.selected] = picker.generator.reroll_capitalization(&picker.page[picker.selected]);
+            }
+            KeyCode::Char('m') => picker.switch_mode(0),
+            KeyCode::Char('M') => picker.switch_mode(1),
+            _ => {}
+        }
+    }
+}
+
+/// The generator in its current mode, its page and what is on screen
+struct Picker<'a> {
+    config: &'a Config,
+    t: &'a Messages,
+    generator: Generator,
+    page: Vec<Passphrase>,
+    selected: usize,
+    /// Why the last key did nothing, shown above the help line
+    status: Option<String>,
+}
+
+impl Picker<'_> {
+    /// Show a new page with the first candidate selected
+    fn show(&mut self, page: Vec<Passphrase>) {
+        self.page = page;
+        self.selected = 0;
+    }
+
+    /// Step the mode of the front (`part` 0) or back part to the next one
+    /// and draw a new page
+    ///
+    /// The parts of a candidate may be too alike in the new mode, e.g. both
+    /// "ct", so the page is drawn anew rather than re-rendered. Modes the
+    /// settings cannot generate in are skipped, and the first reason shown.
+    fn switch_mode(&mut self, part: usize) {
+        let segments = &self.page[self.selected].segments;
+        let mut modes = [segments[0].mode.clone(), segments[segments.len() - 1].mode.clone()];
+        let mut first_error = None;
+        for _ in 1..MODES.len() {
+            let position = MODES.iter().position(|mode| *mode == modes[part]).unwrap_or(0);
+            modes[part] = MODES[(position + 1) % MODES.len()].clone();
+            let switched = with_modes(&self.generator, &modes).build().map_err(|e| generator_error(&e, self.t).message);
+            match switched.and_then(|switched| Ok((new_page(&switched, self.config, self.t)?, switched))) {
+                Ok((page, switched)) => {
+                    self.generator = switched;
+                    self.show(page);
+                    self.status = first_error;
+                    return;
+                }
+                Err(message) => {
+                    first_error.get_or_insert(message);
+                }
+            }
+        }
+        self.status = first_error;
+    }
+
+    /// Draw the page from the top of the screen, cut to its size
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let (columns, rows) = terminal::size()?;
+        let (columns, rows) = (usize::from(columns), usize::from(rows));
+        let t = self.t;
+
+        let password_width = self.page.iter().map(|passphrase| width(&passphrase.password)).max().unwrap_or(0);
+        let mut lines: Vec<(String, bool)> = Vec::new();
+        for (i, passphrase) in self.page.iter().enumerate() {
+            let selected = i == self.selected;
+            let padding = " ".repeat(password_width - width(&passphrase.password));
+            let entropy = fill(t.entropy, &[&format!("{:.1}", passphrase.entropy_bits)]);
+            lines.push((format!("{} {:>2}  {}{}  {}", if selected { '>' } else { ' ' }, i + 1, passphrase.password, padding, entropy), selected));
+            lines.push((format!("      {}", self.generator.source_hint(&passphrase.entries())), false));
+            if selected {
+                for segment in &passphrase.segments {
+                    if let Some(origin) = origin(&segment.entry) {
+                        lines.push((format!("      {}", fill(t.origin, &[&segment.entry.text, &origin])), false));
+                    }
+                }
+            }
+        }
+        // Keep the status and help lines on screen when the page is taller
+        let help = wrap(t.pick_help, columns);
+        lines.truncate(rows.saturating_sub(2 + help.len()));
+        lines.push((String::new(), false));
+        lines.push((self.status.clone().unwrap_or_default(), false));
+        lines.extend(help.into_iter().map(|line| (line, false)));
+
+        queue!(out, MoveTo(0, 0))?;
+        for (line, highlight) in lines.iter().take(rows) {
+            if *highlight {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+            queue!(out, Print(truncate(line, columns)), SetAttribute(Attribute::Reset), Clear(ClearType::UntilNewLine), MoveToNextLine(1))?;
+        }
+        queue!(out, Clear(ClearType::FromCursorDown))?;
+        out.flush()
+    }
+}
+
+/// The terminal in raw mode on the alternate screen, restored when dropped
+struct Screen(io::Stderr);
+
+impl Screen {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let mut stderr = io::stderr();
+        if let Err(e) = execute!(stderr, EnterAlternateScreen, Hide) {
+            terminal::disable_raw_mode().ok();
+            return Err(e);
+        }
+        Ok(Screen(stderr))
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        execute!(self.0, Show, LeaveAlternateScreen).ok();
+        terminal::disable_raw_mode().ok();
+    }
+}
+
+/// Draw a page of different candidates, as many as the settings allow
+fn new_page(generator: &Generator, config: &Config, t: &Messages) -> Result<Vec<Passphrase>, String> {
+    let size = config.count.unwrap_or(PAGE_SIZE).min(generator.batch_capacity(&config.batch));
+    generator
+        .generate_batch(size, &config.batch)
+        .map_err(|e| fill(t.batch_failed, &[&size, &batch_error(&e, t)]))
+}
+
+/// The generator with `[front, back]` modes, as a single mode if they agree
+fn with_modes(generator: &Generator, [front, back]: &[Mode; 2]) -> Generator {
+    let generator = generator.clone();
+    if front != back {
+        return generator.dual_mode(front.clone(), back.clone());
+    }
+    match front {
+        Mode::PinyinFull => generator.full_pinyin(),
+        Mode::PinyinInit => generator.initials(),
+        Mode::Chinese => generator.chinese(),
+        Mode::Zhuyin => generator.zhuyin(),
+    }
+}
+
+/// Columns `text` takes in a terminal, roughly: Chinese characters and
+/// Zhuyin are wide, Latin letters with tone marks are not
+fn width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+fn char_width(ch: char) -> usize {
+    if ch >= '\u{1100}' {
+        2
+    } else {
+        1
+    }
+}
+
+/// `text` broken after commas into lines of at most `columns`, where it can be
+fn wrap(text: &str, columns: usize) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    for piece in text.split_inclusive([',', '，']) {
+        let line = lines.last_mut().unwrap();
+        if !line.is_empty() && width(line) + width(piece.trim_end()) > columns {
+            lines.push(piece.trim_start().to_string());
+        } else {
+            line.push_str(piece);
+        }
+    }
+    lines
+}
+
+/// `text` cut to fit in `columns`
+fn truncate(text: &str, columns: usize) -> &str {
+    let mut used = 0;
+    for (i, ch) in text.char_indices() {
+        used += char_width(ch);
+        if used > columns {
+            return &text[..i];
+        }
+    }
+    text
+}
@@ -0,0 +1,82 @@
+//! Named profiles from the configuration file
+//!
+//! The file is TOML with `[profiles.NAME]` tables, where each key is a long
+//! command line option without the leading `--`. A top-level
+//! `default-profile` names the profile used when `--profile` is not given.
+//!
+//! ```toml
+//! default-profile = "daily"
+//!
+//! [profiles.daily]
+//! dual = true
+//! separator = "_"
+//!
+//! [profiles.work-vpn]
+//! chengyu = true
+//! modes = ["full", "init"]
+//! random-caps = true
+//! tier = "famous"
+//! ```
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::path::PathBuf;
+
+/// A value of a profile setting
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(untagged, expecting = "expected a string, number, boolean or array of them")]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    /// The value as a command line option argument; arrays are joined with
+    /// commas, so `[4, 2, 1]` becomes "4,2,1"
+    pub fn to_arg(&self) -> String {
+        match self {
+            Value::String(text) => text.clone(),
+            Value::Integer(n) => n.to_string(),
+            Value::Float(x) => x.to_string(),
+            Value::Bool(b) => b.to_string(),
+            Value::Array(items) => items.iter().map(Value::to_arg).collect::<Vec<_>>().join(","),
+        }
+    }
+}
+
+/// The settings of one profile, by option name
+pub type Profile = BTreeMap<String, Value>;
+
+/// The profiles of a configuration file
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profiles {
+    #[serde(rename = "default-profile")]
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Profiles {
+    pub fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.get(name)
+    }
+}
+
+/// `$XDG_CONFIG_HOME/poetry-pass/config.toml`, or
+/// `~/.config/poetry-pass/config.toml`
+pub fn default_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("poetry-pass").join("config.toml"))
+}
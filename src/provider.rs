@@ -1,88 +1,492 @@
+use crate::corpus::{Corpus, Entry, Tier};
+use crate::error::Error;
+use rand::distr::weighted::WeightedIndex;
+use rand::distr::Distribution;
 use rand::seq::IndexedRandom;
-use rand::Rng;
+use std::ops::RangeInclusive;
+use std::sync::OnceLock;
 
+/// Where password segments are drawn from
+///
+/// With the `serde` feature, built-in sources serialize by name, e.g.
+/// `"song_ci"`, and custom corpora with all of their entries.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Source {
     Poetry,      // Poetry lines
     Words,       // Words list
-    Mixed,       // Mixed source (70% words, 30% poetry)
+    Mixed,       // Front: 70% words, 30% poetry, Back: poetry
     WordsPoetry, // Front: words, Back: poetry
     PoetryWords, // Front: poetry, Back: words
     WordsWords,  // Front: words, Back: words
     PoetryPoetry, // Front: poetry, Back: poetry
+    Chengyu,     // Four-character idioms (成语)
+    SongCi,      // Song ci (宋词) lines of 3-11 characters
+    YuanQu,      // Yuan qu (元曲) lines of 3-11 characters
+    LunYu,       // Quotations from the Analects (论语)
+    DaoDeJing,   // Quotations from the Tao Te Ching (道德经)
+    ShiJing,     // Lines of the Book of Songs (诗经)
+    GuWen,       // Classical prose quotations (古文观止 and others)
+    Custom(Corpus), // Corpus loaded at runtime
+    Pair(Box<Source>, Box<Source>), // Front: first source, Back: second source
+    Union(Vec<Source>), // Every entry of the sources' corpora equally likely
+    Weighted(Vec<(Source, f64)>), // Sources picked with these relative weights
 }
 
-pub struct Provider {
-    source: Source,
+/// The part of the password a segment is drawn for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Part {
+    Front,
+    Back,
 }
 
-impl Provider {
-    pub fn new(source: Source) -> Self {
-        Provider { source }
-    }
-    
-    pub fn get(&self) -> &'static str {
-        let mut rng = rand::rng();
-        match self.source {
-            Source::Poetry => {
-                crate::POETRY.choose(&mut rng)
-                    .expect("No poetry data available")
+impl Source {
+    /// Corpora the front part is drawn from, with their probabilities
+    pub fn front_corpora(&self) -> Vec<(&Corpus, f64)> {
+        match self {
+            Source::Poetry | Source::PoetryWords | Source::PoetryPoetry => {
+                vec![(Corpus::poetry(), 1.0)]
             }
-            Source::Words => {
-                crate::WORDS.choose(&mut rng)
-                    .expect("No words data available")
+            Source::Words | Source::WordsPoetry | Source::WordsWords => {
+                vec![(Corpus::words(), 1.0)]
             }
-            Source::Mixed => {
-                if rng.random_ratio(7, 10) {
-                    crate::WORDS.choose(&mut rng).unwrap()
-                } else {
-                    crate::POETRY.choose(&mut rng).unwrap()
-                }
+            Source::Mixed => vec![(Corpus::words(), 0.7), (Corpus::poetry(), 0.3)],
+            Source::Chengyu => vec![(Corpus::chengyu(), 1.0)],
+            Source::SongCi => vec![(Corpus::song_ci(), 1.0)],
+            Source::YuanQu => vec![(Corpus::yuan_qu(), 1.0)],
+            Source::LunYu => vec![(Corpus::lun_yu(), 1.0)],
+            Source::DaoDeJing => vec![(Corpus::dao_de_jing(), 1.0)],
+            Source::ShiJing => vec![(Corpus::shi_jing(), 1.0)],
+            Source::GuWen => vec![(Corpus::gu_wen(), 1.0)],
+            Source::Custom(corpus) => vec![(corpus, 1.0)],
+            Source::Pair(front_source, _) => front_source.front_corpora(),
+            Source::Union(sources) => union_corpora(sources.iter().map(Source::front_corpora)),
+            Source::Weighted(sources) => weighted_corpora(sources, Source::front_corpora),
+        }
+    }
+
+    /// Corpora the back part is drawn from, with their probabilities
+    pub fn back_corpora(&self) -> Vec<(&Corpus, f64)> {
+        match self {
+            Source::Poetry | Source::WordsPoetry | Source::PoetryPoetry | Source::Mixed => {
+                vec![(Corpus::poetry(), 1.0)]
             }
-            // For specific source combinations, use get_front() method
-            Source::WordsPoetry | Source::PoetryWords | 
-            Source::WordsWords | Source::PoetryPoetry => {
-                self.get_front()
+            Source::Words | Source::PoetryWords | Source::WordsWords => {
+                vec![(Corpus::words(), 1.0)]
             }
+            Source::Chengyu => vec![(Corpus::chengyu(), 1.0)],
+            Source::SongCi => vec![(Corpus::song_ci(), 1.0)],
+            Source::YuanQu => vec![(Corpus::yuan_qu(), 1.0)],
+            Source::LunYu => vec![(Corpus::lun_yu(), 1.0)],
+            Source::DaoDeJing => vec![(Corpus::dao_de_jing(), 1.0)],
+            Source::ShiJing => vec![(Corpus::shi_jing(), 1.0)],
+            Source::GuWen => vec![(Corpus::gu_wen(), 1.0)],
+            Source::Custom(corpus) => vec![(corpus, 1.0)],
+            Source::Pair(_, back_source) => back_source.back_corpora(),
+            Source::Union(sources) => union_corpora(sources.iter().map(Source::back_corpora)),
+            Source::Weighted(sources) => weighted_corpora(sources, Source::back_corpora),
         }
     }
-    
-    /// Get front part based on source type
-    pub fn get_front(&self) -> &'static str {
-        let mut rng = rand::rng();
-        match self.source {
-            Source::WordsPoetry | Source::WordsWords => {
-                crate::WORDS.choose(&mut rng)
-                    .expect("No words data available")
+
+    /// Every entry the front part can be drawn from, with its probability
+    ///
+    /// Unlike `front_corpora`, this applies the length and tier limits of
+    /// `sampling`, so the probabilities are exactly those of the provider.
+    pub fn front_candidates(&self, sampling: &Sampling) -> Vec<(&Entry, f64)> {
+        self.part_candidates(Part::Front, sampling)
+    }
+
+    /// Every entry the back part can be drawn from, with its probability
+    pub fn back_candidates(&self, sampling: &Sampling) -> Vec<(&Entry, f64)> {
+        self.part_candidates(Part::Back, sampling)
+    }
+
+    /// Whether the weights of `Weighted` sources, however deeply nested, are
+    /// finite, non-negative and not all zero
+    pub(crate) fn has_valid_weights(&self) -> bool {
+        match self {
+            Source::Pair(front_source, back_source) => {
+                front_source.has_valid_weights() && back_source.has_valid_weights()
             }
-            Source::PoetryWords | Source::PoetryPoetry => {
-                crate::POETRY.choose(&mut rng)
-                    .expect("No poetry data available")
+            Source::Union(sources) => sources.iter().all(Source::has_valid_weights),
+            Source::Weighted(sources) => {
+                sources.iter().all(|(source, weight)| weight.is_finite() && *weight >= 0.0 && source.has_valid_weights())
+                    && sources.iter().any(|&(_, weight)| weight > 0.0)
             }
-            _ => self.get() // Fallback to original logic
+            _ => true,
         }
     }
-    
-    /// Get back part based on source type
-    pub fn get_back(&self) -> &'static str {
-        let mut rng = rand::rng();
-        match self.source {
-            Source::WordsPoetry | Source::PoetryPoetry => {
-                crate::POETRY.choose(&mut rng)
-                    .expect("No poetry data available")
+
+    fn corpora(&self, part: Part) -> Vec<(&Corpus, f64)> {
+        match part {
+            Part::Front => self.front_corpora(),
+            Part::Back => self.back_corpora(),
+        }
+    }
+
+    fn part_candidates(&self, part: Part, sampling: &Sampling) -> Vec<(&Entry, f64)> {
+        match self {
+            Source::Pair(front_source, _) if part == Part::Front => front_source.part_candidates(part, sampling),
+            Source::Pair(_, back_source) => back_source.part_candidates(part, sampling),
+            Source::Union(_) => {
+                // One pool: every pickable entry weighs the same, whatever
+                // corpus it comes from
+                let pool: Vec<(&Corpus, f64)> = self
+                    .corpora(part)
+                    .into_iter()
+                    .map(|(corpus, _)| (corpus, corpus.entries().iter().map(|e| sampling.weight(e)).sum()))
+                    .collect();
+                candidates(&pool, sampling)
             }
-            Source::PoetryWords | Source::WordsWords => {
-                crate::WORDS.choose(&mut rng)
-                    .expect("No words data available")
+            Source::Weighted(sources) => {
+                let members: Vec<(Vec<(&Entry, f64)>, f64)> = sources
+                    .iter()
+                    .filter(|&&(_, weight)| weight > 0.0)
+                    .map(|(source, weight)| (source.part_candidates(part, sampling), *weight))
+                    .filter(|(entries, _)| !entries.is_empty())
+                    .collect();
+                let total: f64 = members.iter().map(|&(_, weight)| weight).sum();
+                members
+                    .into_iter()
+                    .flat_map(|(entries, weight)| {
+                        entries.into_iter().map(move |(entry, p)| (entry, p * weight / total))
+                    })
+                    .collect()
             }
-            _ => self.get_poetry() // Fallback to poetry for Mixed mode
+            _ => candidates(&self.corpora(part), sampling),
+        }
+    }
+}
+
+/// The distinct corpora of several lists, weighted by their size so that
+/// every entry is equally likely
+fn union_corpora<'a, I>(lists: I) -> Vec<(&'a Corpus, f64)>
+where
+    I: Iterator<Item = Vec<(&'a Corpus, f64)>>,
+{
+    let mut corpora: Vec<&Corpus> = Vec::new();
+    for (corpus, _) in lists.flatten() {
+        // Clones of a corpus share their entries
+        if !corpora.iter().any(|seen| std::ptr::eq(seen.entries(), corpus.entries())) {
+            corpora.push(corpus);
+        }
+    }
+    let total: usize = corpora.iter().map(|corpus| corpus.len()).sum();
+    corpora
+        .into_iter()
+        .filter(|corpus| !corpus.is_empty())
+        .map(|corpus| (corpus, corpus.len() as f64 / total as f64))
+        .collect()
+}
+
+/// The corpora of weighted sources, each source's probabilities scaled by
+/// its share of the weights
+fn weighted_corpora<'a, F>(sources: &'a [(Source, f64)], corpora_of: F) -> Vec<(&'a Corpus, f64)>
+where
+    F: Fn(&'a Source) -> Vec<(&'a Corpus, f64)>,
+{
+    let total: f64 = sources.iter().map(|&(_, weight)| weight.max(0.0)).sum();
+    sources
+        .iter()
+        .filter(|&&(_, weight)| weight > 0.0)
+        .flat_map(|(source, weight)| {
+            corpora_of(source).into_iter().map(move |(corpus, p)| (corpus, p * weight / total))
+        })
+        .collect()
+}
+
+/// Which entries segments are drawn from, and how likely each one is
+#[derive(Clone, Debug, Default)]
+pub struct Sampling {
+    /// Only entries whose text length in characters is within this range
+    pub length: Option<RangeInclusive<usize>>,
+    /// Relative weights of `Famous`, `Common` and `Obscure` entries; a weight
+    /// of `0.0` leaves the tier out. Entries without a tier count as `Common`.
+    pub tier_weights: Option<[f64; 3]>,
+}
+
+impl Sampling {
+    /// Relative weight of an entry within its corpus, `0.0` if it is never picked
+    pub fn weight(&self, entry: &Entry) -> f64 {
+        if self.length.as_ref().is_some_and(|length| !length.contains(&entry.char_count())) {
+            return 0.0;
+        }
+        match self.tier_weights {
+            Some(weights) => weights[entry.tier.unwrap_or(Tier::Common) as usize],
+            None => 1.0,
         }
     }
-    
+
+    fn is_unrestricted(&self) -> bool {
+        self.length.is_none() && self.tier_weights.is_none()
+    }
+}
+
+pub struct Provider {
+    source: Source,
+    sampling: Sampling,
+    /// Candidates of the front and back parts, worked out on first use
+    prepared: [OnceLock<Prepared>; 2],
+}
+
+impl Provider {
+    pub fn new(source: Source) -> Self {
+        Provider::with_sampling(source, Sampling::default())
+    }
+
+    /// Only pick entries whose text length in characters is within `length`
+    pub fn with_length(source: Source, length: RangeInclusive<usize>) -> Self {
+        Provider::with_sampling(source, Sampling { length: Some(length), ..Sampling::default() })
+    }
+
+    /// Pick entries as `sampling` allows
+    pub fn with_sampling(source: Source, sampling: Sampling) -> Self {
+        Provider { source, sampling, prepared: Default::default() }
+    }
+
+    fn prepared(&self, part: Part) -> &Prepared {
+        self.prepared[part as usize].get_or_init(|| {
+            Prepared::new(&self.source.corpora(part), &self.source.part_candidates(part, &self.sampling))
+        })
+    }
+
+    /// Every entry `part` is drawn from with its probability, as
+    /// [`Source::front_candidates`] gives them but without scanning the
+    /// corpora again
+    pub(crate) fn candidates(&self, part: Part) -> Vec<(&Entry, f64)> {
+        let prepared = self.prepared(part);
+        let corpora = self.source.corpora(part);
+        (0..prepared.entries.len()).map(|i| (prepared.entry(&corpora, i), prepared.probabilities[i])).collect()
+    }
+
+    /// Draw an entry for `part` as if the entries `accept` rejects were not
+    /// there, or why there is none
+    ///
+    /// Gives [`Error::NoDistinctBack`] naming `other`, the text of the other
+    /// part, if `accept` rejects every candidate.
+    pub(crate) fn pick_where<F: Fn(&Entry) -> bool>(&self, part: Part, other: &str, accept: F) -> Result<&Entry, Error> {
+        let prepared = self.prepared(part);
+        let corpora = self.source.corpora(part);
+        let Some(index) = &prepared.index else {
+            return Err(no_candidates(&corpora, &self.sampling));
+        };
+        let entry = |i| prepared.entry(&corpora, i);
+        draw_where(index, prepared.entries.len(), |i| prepared.probabilities[i], |i| accept(entry(i)))
+            .map(entry)
+            .ok_or_else(|| Error::NoDistinctBack { front: other.to_string(), corpora: corpus_names(&corpora) })
+    }
+
+    pub fn get(&self) -> &str {
+        // For specific source combinations this is the front part
+        &self.get_front_entry().text
+    }
+
+    /// Get front part based on source type
+    pub fn get_front(&self) -> &str {
+        &self.get_front_entry().text
+    }
+
+    /// Get back part based on source type
+    pub fn get_back(&self) -> &str {
+        &self.get_back_entry().text
+    }
+
+    /// Get front part with its author and title, if known
+    ///
+    /// Panics if there is nothing to draw from; see
+    /// [`try_front_entry`](Self::try_front_entry).
+    pub fn get_front_entry(&self) -> &Entry {
+        self.try_front_entry().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Get back part with its author and title, if known
+    ///
+    /// Panics if there is nothing to draw from; see
+    /// [`try_back_entry`](Self::try_back_entry).
+    pub fn get_back_entry(&self) -> &Entry {
+        self.try_back_entry().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Get front part, or why there is none
+    pub fn try_front_entry(&self) -> Result<&Entry, Error> {
+        self.pick_where(Part::Front, "", |_| true)
+    }
+
+    /// Get back part, or why there is none
+    pub fn try_back_entry(&self) -> Result<&Entry, Error> {
+        self.pick_where(Part::Back, "", |_| true)
+    }
+
+    /// Get back part accepted by `accept`, or [`Error::NoDistinctBack`]
+    /// naming `front` if it rejects every candidate
+    pub fn try_back_entry_where<F: Fn(&Entry) -> bool>(&self, front: &str, accept: F) -> Result<&Entry, Error> {
+        self.pick_where(Part::Back, front, accept)
+    }
+
     /// Get a random poetry line
+    ///
+    /// Panics if no poetry corpus has a line to draw, which only happens
+    /// when the built-in data is empty.
     pub fn get_poetry(&self) -> &'static str {
-        let mut rng = rand::rng();
-        crate::POETRY.choose(&mut rng)
-            .expect("No poetry data available")
+        let sampling = Sampling::default();
+        let entry = choose(Source::Poetry.front_candidates(&sampling), || Source::Poetry.front_corpora(), &sampling);
+        &entry.unwrap_or_else(|e| panic!("{}", e)).text
+    }
+}
+
+/// Every entry a segment can be drawn from, with its probability
+///
+/// Corpora without a pickable entry are dropped and the probabilities of
+/// the others renormalized; within a corpus entries are picked in proportion
+/// to their `Sampling::weight`. This is the distribution segments are
+/// actually drawn from, shared by the provider and the entropy estimate.
+pub fn candidates<'a>(corpora: &[(&'a Corpus, f64)], sampling: &Sampling) -> Vec<(&'a Entry, f64)> {
+    let mut weighted = Vec::new();
+    for &(corpus, weight) in corpora {
+        if weight <= 0.0 {
+            continue;
+        }
+        let entries: Vec<(&Entry, f64)> = corpus
+            .entries()
+            .iter()
+            .map(|entry| (entry, sampling.weight(entry)))
+            .filter(|&(_, entry_weight)| entry_weight > 0.0)
+            .collect();
+        let total: f64 = entries.iter().map(|&(_, entry_weight)| entry_weight).sum();
+        if total > 0.0 {
+            weighted.push((entries, weight, total));
+        }
+    }
+
+    let corpus_total: f64 = weighted.iter().map(|&(_, weight, _)| weight).sum();
+    weighted
+        .into_iter()
+        .flat_map(|(entries, weight, total)| {
+            entries
+                .into_iter()
+                .map(move |(entry, entry_weight)| (entry, weight / corpus_total * entry_weight / total))
+        })
+        .collect()
+}
+
+/// Draw from `candidates` as if the entries `accept` rejects were not there,
+/// or `None` if it rejects all of them
+pub fn choose_where<'a, F>(candidates: &[(&'a Entry, f64)], accept: F) -> Option<&'a Entry>
+where
+    F: Fn(&Entry) -> bool,
+{
+    Picker::new(candidates.to_vec()).pick_where(accept)
+}
+
+/// Candidates prepared once for many draws, e.g. for a batch of passwords
+pub struct Picker<'a> {
+    candidates: Vec<(&'a Entry, f64)>,
+    index: Option<WeightedIndex<f64>>,
+}
+
+impl<'a> Picker<'a> {
+    pub fn new(candidates: Vec<(&'a Entry, f64)>) -> Self {
+        let index = WeightedIndex::new(candidates.iter().map(|&(_, p)| p)).ok();
+        Picker { candidates, index }
+    }
+
+    /// Draw an entry as if the entries `accept` rejects were not there, or
+    /// `None` if it rejects all of them
+    pub fn pick_where<F: Fn(&Entry) -> bool>(&self, accept: F) -> Option<&'a Entry> {
+        let index = self.index.as_ref()?;
+        let candidates = &self.candidates;
+        draw_where(index, candidates.len(), |i| candidates[i].1, |i| accept(candidates[i].0)).map(|i| candidates[i].0)
+    }
+}
+
+/// Candidates of a part kept by a [`Provider`]
+///
+/// Entries are stored as the position of their corpus among the part's
+/// corpora and their index there, since the provider owns those corpora.
+struct Prepared {
+    entries: Vec<(usize, usize)>,
+    probabilities: Vec<f64>,
+    index: Option<WeightedIndex<f64>>,
+}
+
+impl Prepared {
+    fn new(corpora: &[(&Corpus, f64)], candidates: &[(&Entry, f64)]) -> Self {
+        let (entries, probabilities): (Vec<_>, Vec<_>) = candidates
+            .iter()
+            .map(|&(entry, p)| (position(corpora, entry).expect("candidates come from the part's corpora"), p))
+            .unzip();
+        let index = WeightedIndex::new(&probabilities).ok();
+        Prepared { entries, probabilities, index }
     }
+
+    fn entry<'a>(&self, corpora: &[(&'a Corpus, f64)], i: usize) -> &'a Entry {
+        let (corpus, offset) = self.entries[i];
+        &corpora[corpus].0.entries()[offset]
+    }
+}
+
+/// Draw one of `len` candidates with `index` as if the ones `accept`
+/// rejects were not there, or `None` if it rejects all of them
+fn draw_where<P, F>(index: &WeightedIndex<f64>, len: usize, probability: P, accept: F) -> Option<usize>
+where
+    P: Fn(usize) -> f64,
+    F: Fn(usize) -> bool,
+{
+    let mut rng = rand::rng();
+    // Rejections are usually rare, so try a few draws before filtering everything
+    for _ in 0..16 {
+        let i = index.sample(&mut rng);
+        if accept(i) {
+            return Some(i);
+        }
+    }
+    let accepted: Vec<usize> = (0..len).filter(|&i| accept(i)).collect();
+    accepted.choose_weighted(&mut rng, |&i| probability(i)).ok().copied()
+}
+
+fn choose<'a, F>(candidates: Vec<(&'a Entry, f64)>, corpora: F, sampling: &Sampling) -> Result<&'a Entry, Error>
+where
+    F: FnOnce() -> Vec<(&'a Corpus, f64)>,
+{
+    let mut rng = rand::rng();
+    candidates
+        .choose_weighted(&mut rng, |&(_, p)| p)
+        .map(|&(entry, _)| entry)
+        .map_err(|_| no_candidates(&corpora(), sampling))
+}
+
+/// Why nothing can be drawn from `corpora`: they are empty, or the
+/// sampling limits leave nothing
+pub(crate) fn no_candidates(corpora: &[(&Corpus, f64)], sampling: &Sampling) -> Error {
+    let names = corpus_names(corpora);
+    if sampling.is_unrestricted() || corpora.iter().all(|(corpus, _)| corpus.is_empty()) {
+        Error::EmptyCorpus { corpora: names }
+    } else {
+        Error::NoCandidates { corpora: names }
+    }
+}
+
+/// The name of the corpus `entry` belongs to and its index there
+///
+/// Entries are matched by address, so this finds the entries the corpora
+/// hand out themselves, not equal copies of them.
+pub(crate) fn locate(corpora: &[(&Corpus, f64)], entry: &Entry) -> Option<(String, usize)> {
+    position(corpora, entry).map(|(corpus, offset)| (corpora[corpus].0.name().to_string(), offset))
+}
+
+/// The position of the corpus `entry` belongs to among `corpora` and its
+/// index there, matched by address like [`locate`]
+fn position(corpora: &[(&Corpus, f64)], entry: &Entry) -> Option<(usize, usize)> {
+    let address = entry as *const Entry as usize;
+    corpora.iter().enumerate().find_map(|(corpus, (own_corpus, _))| {
+        let entries = own_corpus.entries();
+        let offset = address.checked_sub(entries.as_ptr() as usize)? / std::mem::size_of::<Entry>();
+        let own = entries.get(offset).is_some_and(|own| std::ptr::eq(own, entry));
+        own.then_some((corpus, offset))
+    })
+}
+
+fn corpus_names(corpora: &[(&Corpus, f64)]) -> String {
+    corpora.iter().map(|(corpus, _)| corpus.name()).collect::<Vec<_>>().join("/")
 }
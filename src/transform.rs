@@ -1,25 +1,43 @@
+use crate::corpus::Entry;
 use pinyin::ToPinyin as _;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Mode {
+    #[default]
     PinyinFull,  // Full Pinyin (default)
     PinyinInit,  // Initials
     Chinese,     // Original Chinese
+    Zhuyin,      // Zhuyin (注音符号) with tone marks
 }
 
-impl Default for Mode {
-    fn default() -> Self {
-        Mode::PinyinFull  // Default to Full Pinyin
+impl Mode {
+    /// Every mode, in the order representations are listed
+    pub const ALL: [Mode; 4] = [Mode::Chinese, Mode::PinyinFull, Mode::PinyinInit, Mode::Zhuyin];
+
+    /// Stable name for machine-readable output: `pinyin_full`,
+    /// `pinyin_init`, `chinese` or `zhuyin`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::PinyinFull => "pinyin_full",
+            Mode::PinyinInit => "pinyin_init",
+            Mode::Chinese => "chinese",
+            Mode::Zhuyin => "zhuyin",
+        }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DualMode {
     pub front: Mode,
     pub back: Mode,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum TransformMode {
     Single(Mode), 
     Dual(DualMode),
@@ -49,9 +67,116 @@ pub fn transform(text: &str, mode: &Mode) -> String {
                 }))
                 .collect()
         },
+        
+        Mode::Zhuyin => {
+            text.to_pinyin()
+                .filter_map(|p| p.map(|py| zhuyin_syllable(py.with_tone())))
+                .collect()
+        },
+    }
+}
+
+/// Transform a corpus entry, preferring its recorded pinyin (which gets
+/// polyphonic characters right) over the `pinyin` crate's reading
+pub fn transform_entry(entry: &Entry, mode: &Mode) -> String {
+    let Some(pinyin) = &entry.pinyin else {
+        return transform(&entry.text, mode);
+    };
+    let syllables = pinyin.split_whitespace().map(plain_syllable);
+    match mode {
+        Mode::Chinese => entry.text.clone(),
+        Mode::PinyinFull => syllables.collect(),
+        Mode::PinyinInit => {
+            syllables.map(|py| py.chars().next().unwrap_or('?')).collect()
+        }
+        Mode::Zhuyin => pinyin.split_whitespace().map(zhuyin_syllable).collect(),
+    }
+}
+
+/// Convert a tone-marked pinyin syllable such as "zhōng" to Zhuyin
+/// ("ㄓㄨㄥ"); syllables without a Zhuyin spelling are kept as they are
+fn zhuyin_syllable(syllable: &str) -> String {
+    let tone = syllable.chars().find_map(|c| match c {
+        'ā' | 'ē' | 'ī' | 'ō' | 'ū' | 'ǖ' => Some(""),
+        'á' | 'é' | 'í' | 'ó' | 'ú' | 'ǘ' | 'ń' | 'ḿ' => Some("ˊ"),
+        'ǎ' | 'ě' | 'ǐ' | 'ǒ' | 'ǔ' | 'ǚ' | 'ň' => Some("ˇ"),
+        'à' | 'è' | 'ì' | 'ò' | 'ù' | 'ǜ' | 'ǹ' => Some("ˋ"),
+        _ => None,
+    });
+    let plain = plain_syllable(syllable).replace('v', "ü");
+    
+    const INITIALS: [(&str, &str); 21] = [
+        ("zh", "ㄓ"), ("ch", "ㄔ"), ("sh", "ㄕ"), ("b", "ㄅ"), ("p", "ㄆ"), ("m", "ㄇ"), ("f", "ㄈ"),
+        ("d", "ㄉ"), ("t", "ㄊ"), ("n", "ㄋ"), ("l", "ㄌ"), ("g", "ㄍ"), ("k", "ㄎ"), ("h", "ㄏ"),
+        ("j", "ㄐ"), ("q", "ㄑ"), ("x", "ㄒ"), ("r", "ㄖ"), ("z", "ㄗ"), ("c", "ㄘ"), ("s", "ㄙ"),
+    ];
+    let (initial, rest) = INITIALS.iter()
+        .find_map(|&(latin, zhuyin)| plain.strip_prefix(latin).filter(|rest| !rest.is_empty()).map(|rest| (zhuyin, rest)))
+        .unwrap_or(("", plain.as_str()));
+    
+    // Spell out the finals that pinyin abbreviates
+    let full_final = match (initial, rest) {
+        ("ㄓ" | "ㄔ" | "ㄕ" | "ㄖ" | "ㄗ" | "ㄘ" | "ㄙ", "i") => String::new(),
+        ("ㄐ" | "ㄑ" | "ㄒ", _) if rest.starts_with('u') => rest.replacen('u', "ü", 1),
+        ("", _) if rest.starts_with("yu") => rest.replacen("yu", "ü", 1),
+        ("", _) if rest.starts_with("yi") || rest.starts_with("wu") => rest[1..].to_string(),
+        ("", _) if rest.starts_with('y') => rest.replacen('y', "i", 1),
+        ("", _) if rest.starts_with('w') => rest.replacen('w', "u", 1),
+        (_, "iu") => "iou".to_string(),
+        (_, "ui") => "uei".to_string(),
+        (_, "un") => "uen".to_string(),
+        _ => rest.to_string(),
+    };
+    
+    const FINALS: [(&str, &str); 38] = [
+        ("a", "ㄚ"), ("o", "ㄛ"), ("e", "ㄜ"), ("ê", "ㄝ"), ("ai", "ㄞ"), ("ei", "ㄟ"), ("ao", "ㄠ"),
+        ("ou", "ㄡ"), ("an", "ㄢ"), ("en", "ㄣ"), ("ang", "ㄤ"), ("eng", "ㄥ"), ("er", "ㄦ"), ("ong", "ㄨㄥ"),
+        ("i", "ㄧ"), ("ia", "ㄧㄚ"), ("io", "ㄧㄛ"), ("ie", "ㄧㄝ"), ("iao", "ㄧㄠ"), ("iou", "ㄧㄡ"),
+        ("ian", "ㄧㄢ"), ("in", "ㄧㄣ"), ("iang", "ㄧㄤ"), ("ing", "ㄧㄥ"), ("iong", "ㄩㄥ"),
+        ("u", "ㄨ"), ("ua", "ㄨㄚ"), ("uo", "ㄨㄛ"), ("uai", "ㄨㄞ"), ("uei", "ㄨㄟ"), ("uan", "ㄨㄢ"),
+        ("uen", "ㄨㄣ"), ("uang", "ㄨㄤ"), ("ueng", "ㄨㄥ"), ("ü", "ㄩ"), ("üe", "ㄩㄝ"), ("üan", "ㄩㄢ"),
+        ("ün", "ㄩㄣ"),
+    ];
+    let zhuyin = if full_final.is_empty() {
+        Some("")
+    } else {
+        FINALS.iter().find(|&&(latin, _)| latin == full_final).map(|&(_, zhuyin)| zhuyin)
+    };
+    match (zhuyin, tone) {
+        (None, _) => plain,
+        // No tone mark: the neutral tone
+        (Some(zhuyin), None) => format!("˙{}{}", initial, zhuyin),
+        (Some(zhuyin), Some(tone)) => format!("{}{}{}", initial, zhuyin, tone),
     }
 }
 
+/// Strip tone marks from a syllable, matching `pinyin::Pinyin::plain`
+fn plain_syllable(syllable: &str) -> String {
+    syllable.chars().map(|c| match c {
+        'ā' | 'á' | 'ǎ' | 'à' => 'a',
+        'ē' | 'é' | 'ě' | 'è' => 'e',
+        'ī' | 'í' | 'ǐ' | 'ì' => 'i',
+        'ō' | 'ó' | 'ǒ' | 'ò' => 'o',
+        'ū' | 'ú' | 'ǔ' | 'ù' => 'u',
+        'ǖ' | 'ǘ' | 'ǚ' | 'ǜ' => 'ü',
+        'ń' | 'ň' | 'ǹ' => 'n',
+        'ḿ' => 'm',
+        _ => c.to_ascii_lowercase(),
+    }).collect()
+}
+
+pub fn transform_entries(entries: &[&Entry], transform_mode: &TransformMode) -> Vec<String> {
+    entries.iter().enumerate().map(|(i, entry)| {
+        let mode = match transform_mode {
+            TransformMode::Single(mode) => mode,
+            TransformMode::Dual(dual_mode) => {
+                if i == 0 { &dual_mode.front } else { &dual_mode.back }
+            }
+        };
+        transform_entry(entry, mode)
+    }).collect()
+}
+
 pub fn transform_dual(parts: &[&str], dual_mode: &DualMode) -> Vec<String> {
     parts.iter().enumerate().map(|(i, text)| {
         let mode = if i == 0 { &dual_mode.front } else { &dual_mode.back };
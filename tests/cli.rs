@@ -0,0 +1,170 @@
+#![cfg(feature = "cli")]
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process::{Command, Output};
+
+    /// 运行命令行程序：英文提示，不读取用户的配置文件
+    fn run(args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_poetry-pass"))
+            .args(["--lang", "en"])
+            .args(args)
+            .env("XDG_CONFIG_HOME", env!("CARGO_TARGET_TMPDIR"))
+            .output()
+            .unwrap()
+    }
+
+    /// 写入测试用的配置文件
+    fn config(name: &str, text: &str) -> String {
+        let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+        fs::write(&path, text).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    fn stdout(output: &Output) -> String {
+        String::from_utf8(output.stdout.clone()).unwrap()
+    }
+
+    fn stderr(output: &Output) -> String {
+        String::from_utf8(output.stderr.clone()).unwrap()
+    }
+
+    #[test]
+    fn test_option_values() {
+        // --flag=value 与 --flag value 相同
+        let output = run(&["--count=3"]);
+        assert_eq!(output.status.code(), Some(0));
+        assert_eq!(stdout(&output).lines().count(), 3);
+
+        let output = run(&["--separator", "_", "--no-number"]);
+        assert_eq!(output.status.code(), Some(0));
+        assert_eq!(stdout(&output).trim().split('_').count(), 2);
+
+        // 短选项可以合写，最后一个可以直接带值
+        for args in [&["-dn5"][..], &["-dn", "5"], &["-d", "-n5"], &["-dn=5"]] {
+            let output = run(args);
+            assert_eq!(output.status.code(), Some(0), "{:?}", args);
+            assert_eq!(stdout(&output).lines().count(), 5, "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_usage_errors() {
+        // 缺少值、未知选项、多余的值和无效的值都是用法错误，状态为 2
+        let cases: [(&[&str], &str); 5] = [
+            (&["-n"], "-n needs a value"),
+            (&["--count"], "--count needs a value"),
+            (&["--bogus"], "Unknown option: --bogus"),
+            (&["-s=1"], "-s does not take a value"),
+            (&["-n", "0"], "-n must be"),
+        ];
+        for (args, message) in cases {
+            let output = run(args);
+            assert_eq!(output.status.code(), Some(2), "{:?}", args);
+            assert!(stderr(&output).contains(message), "{:?}: {}", args, stderr(&output));
+            assert!(stderr(&output).contains("--help"));
+            assert!(stdout(&output).is_empty());
+        }
+
+        // 无法生成密码的设置是运行错误，状态为 1
+        let output = run(&["--min-entropy", "500"]);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(stderr(&output).contains("below the required 500.0 bits"));
+
+        // 挑选界面需要终端，不会在管道中等待输入
+        let output = run(&["--pick"]);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(stderr(&output).contains("--pick needs a terminal"));
+        assert!(stdout(&output).is_empty());
+    }
+
+    #[test]
+    fn test_help_wins() {
+        // -h 在任何位置都显示帮助，即使其他选项有误
+        for args in [&["-h"][..], &["--bogus", "-h"], &["--help", "--bogus"], &["-s=1", "-h"], &["-n", "0", "--help"]] {
+            let output = run(args);
+            assert_eq!(output.status.code(), Some(0), "{:?}", args);
+            assert!(stdout(&output).starts_with("Poetry password generator"), "{:?}", args);
+            assert!(stderr(&output).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_profiles() {
+        // 字符串中的 #、两种引号、数组和行尾注释
+        let path = config("profiles.toml", r##"
+default-profile = "daily" # 默认
+[profiles.daily]
+separator = "#"   # 分隔符是 #
+no-number = true
+modes = ["chinese", 'chinese']
+
+[profiles."with space"]  # 带引号的名字
+separator = '_'
+no-number = true
+"##);
+        let output = run(&["--config", &path]);
+        assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+        let password = stdout(&output);
+        let parts: Vec<&str> = password.trim().split('#').collect();
+        assert_eq!(parts.len(), 2);
+        assert!(parts.iter().all(|part| part.chars().all(|c| c as u32 > 127)));
+
+        let output = run(&["--config", &path, "--profile", "with space"]);
+        assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+        assert_eq!(stdout(&output).trim().split('_').count(), 2);
+
+        // 命令行可以撤销 profile 中的开关
+        let path = config("negate.toml", "default-profile = \"all\"\n[profiles.all]\nrandom-caps = true\nno-number = true\n");
+        let output = run(&["--config", &path, "--number", "--no-random-caps", "-n", "20"]);
+        assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+        for password in stdout(&output).lines() {
+            assert_eq!(password, password.to_lowercase());
+            assert!(password.rsplit('-').next().unwrap().parse::<u32>().is_ok(), "{}", password);
+        }
+        let output = run(&["--config", &path, "--no-profile", "-n", "20"]);
+        assert!(stdout(&output).lines().all(|password| password == password.to_lowercase()));
+    }
+
+    #[test]
+    fn test_no_config_location() {
+        // 没有 HOME 和 XDG_CONFIG_HOME 时不读取配置文件，除非指定了 profile
+        let run = |args: &[&str]| {
+            Command::new(env!("CARGO_BIN_EXE_poetry-pass"))
+                .args(["--lang", "en"])
+                .args(args)
+                .env_remove("HOME")
+                .env_remove("XDG_CONFIG_HOME")
+                .output()
+                .unwrap()
+        };
+        let output = run(&[]);
+        assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+        let output = run(&["--profile", "daily"]);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(stderr(&output).contains("No configuration file location"), "{}", stderr(&output));
+        assert!(!stderr(&output).contains('~'));
+    }
+
+    #[test]
+    fn test_profile_errors() {
+        // 错误信息带有文件名和行号，状态为 2
+        let cases = [
+            ("duplicate-table.toml", "[profiles.a]\ndual = true\n\n[profiles.a]\n", ":4: invalid table header: duplicate key"),
+            ("duplicate-key.toml", "[profiles.a]\ndual = true\ndual = false\n", ":3: duplicate key `dual`"),
+            ("syntax.toml", "# 注释\n[profiles.a]\ndual true\n", ":3: expected `.`, `=`"),
+            ("header.toml", "[profiles.a] x\n", ":1: invalid table header"),
+            ("unterminated.toml", "[profiles.a]\nseparator = \"#\n", ":2: invalid basic string"),
+            ("table-value.toml", "[profiles.a]\nlength = { min = 4 }\n", ":2: expected a string, number, boolean or array"),
+            ("top-level.toml", "dual = true\n", ":1: unknown field `dual`"),
+        ];
+        for (name, text, message) in cases {
+            let path = config(name, text);
+            let output = run(&["--config", &path, "--profile", "a"]);
+            assert_eq!(output.status.code(), Some(2), "{}", name);
+            assert!(stderr(&output).contains(&format!("{}{}", name, message)), "{}: {}", name, stderr(&output));
+        }
+    }
+}
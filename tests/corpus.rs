@@ -0,0 +1,182 @@
+#[cfg(test)]
+mod tests {
+    use poetry_pass::{Corpus, Generator, Source};
+    use std::fs;
+
+    #[test]
+    fn test_from_lines_skips_comments_and_blanks() {
+        let corpus = Corpus::from_lines("test", ["# 注释", "", "  春眠不觉晓  ", "处处闻啼鸟"]);
+        assert_eq!(corpus.len(), 2);
+        assert_eq!(corpus.get(0), Some("春眠不觉晓"));
+        assert_eq!(corpus.get(1), Some("处处闻啼鸟"));
+    }
+
+    #[test]
+    fn test_load_file_and_dir() {
+        let dir = std::env::temp_dir().join(format!("poetry-pass-corpus-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.txt"), "# 词语\n花朵\n").unwrap();
+        fs::write(dir.join("b.txt"), "月亮\n\n星星\n").unwrap();
+        fs::write(dir.join("ignored.md"), "不读\n").unwrap();
+
+        let file = Corpus::load(dir.join("a.txt")).unwrap();
+        assert_eq!(file.name(), "a");
+        assert_eq!(file.iter().collect::<Vec<_>>(), ["花朵"]);
+
+        let all = Corpus::load(&dir).unwrap();
+        assert_eq!(all.iter().collect::<Vec<_>>(), ["花朵", "月亮", "星星"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_builtin_corpora() {
+        assert_eq!(Corpus::poetry().len(), poetry_pass::POETRY.len());
+        assert_eq!(Corpus::words().len(), poetry_pass::WORDS.len());
+    }
+
+    #[test]
+    fn test_generate_with_custom_corpus() {
+        let corpus = Corpus::from_lines("custom", ["花朵"]);
+        let (password, source) = Generator::new().corpus(corpus).no_number().allow_repeats().generate_with_source();
+        assert_eq!(password, "huaduo-huaduo");
+        assert_eq!(source, "花朵-花朵");
+    }
+
+    #[test]
+    fn test_custom_corpus_with_builtin() {
+        let corpus = Corpus::from_lines("custom", ["花朵"]);
+        let (_, source) = Generator::new()
+            .sources(Source::Custom(corpus), Source::Poetry)
+            .generate_with_source();
+        let parts: Vec<&str> = source.split('-').collect();
+        assert_eq!(parts[0], "花朵");
+        assert!(poetry_pass::POETRY.contains(&parts[1]));
+    }
+
+    #[test]
+    fn test_chengyu_corpus() {
+        use poetry_pass::transform::transform_entry;
+        use poetry_pass::Mode;
+
+        let corpus = Corpus::chengyu();
+        assert_eq!(corpus.len(), poetry_pass::CHENGYU.len());
+        let index = corpus.iter().position(|text| text == "天长地久").unwrap();
+        let entry = corpus.entry(index).unwrap();
+        assert_eq!(entry.pinyin.as_deref(), Some("tiān cháng dì jiǔ"));
+        assert!(entry.meaning.is_some());
+
+        // 使用词库自带的拼音，多音字“长”读 cháng
+        assert_eq!(transform_entry(entry, &Mode::PinyinFull), "tianchangdijiu");
+        assert_eq!(transform_entry(entry, &Mode::PinyinInit), "tcdj");
+        assert_eq!(transform_entry(entry, &Mode::Chinese), "天长地久");
+    }
+
+    #[test]
+    fn test_chengyu_with_meaning_hint() {
+        let (_, source) = Generator::new()
+            .sources(Source::Chengyu, Source::Poetry)
+            .meaning_hint()
+            .generate_with_source();
+        let idiom = source.split('（').next().unwrap();
+        assert_eq!(idiom.chars().count(), 4);
+        assert!(source.contains('）'));
+    }
+
+    #[test]
+    fn test_song_ci_and_yuan_qu_corpora() {
+        for corpus in [Corpus::song_ci(), Corpus::yuan_qu()] {
+            assert!(!corpus.is_empty());
+            // 保留词曲的自然句长，而不仅是五言、七言
+            assert!(corpus.entries().iter().all(|entry| (3..=11).contains(&entry.char_count())));
+            assert!(corpus.entries().iter().any(|entry| entry.char_count() < 5));
+            assert!(corpus.entries().iter().all(|entry| entry.author.is_some() && entry.title.is_some()));
+        }
+
+        let index = Corpus::yuan_qu().iter().position(|text| text == "枯藤老树昏鸦").unwrap();
+        let entry = Corpus::yuan_qu().entry(index).unwrap();
+        assert_eq!(entry.author.as_deref(), Some("马致远"));
+    }
+
+    #[test]
+    fn test_segment_length() {
+        let generator = Generator::new().song_ci_only().segment_length(4, 4);
+        for _ in 0..20 {
+            let (_, entries) = generator.generate_with_entries();
+            assert!(entries.iter().all(|entry| entry.char_count() == 4));
+        }
+
+        // 限制句长会减少可选的句子，熵随之降低
+        let unrestricted = Generator::new().song_ci_only().entropy_bits();
+        assert!(generator.entropy_bits() < unrestricted);
+    }
+
+    #[test]
+    fn test_segment_length_skips_corpora_without_fitting_lines() {
+        // 混合模式前半部分本来 70% 取词语；词语都不超过四字，
+        // 限制为五到七字时只从诗句中选
+        let generator = Generator::new().segment_length(5, 7);
+        for _ in 0..20 {
+            let (_, entries) = generator.generate_with_entries();
+            assert!(poetry_pass::POETRY.contains(&entries[0].text.as_str()));
+        }
+    }
+
+    #[test]
+    fn test_classical_quotation_corpora() {
+        let corpora = [
+            (Corpus::lun_yu(), poetry_pass::LUN_YU.len(), "学而时习之"),
+            (Corpus::dao_de_jing(), poetry_pass::DAO_DE_JING.len(), "上善若水"),
+            (Corpus::shi_jing(), poetry_pass::SHI_JING.len(), "关关雎鸠"),
+            (Corpus::gu_wen(), poetry_pass::GU_WEN.len(), "落霞与孤鹜齐飞"),
+        ];
+        for (corpus, len, line) in corpora {
+            assert_eq!(corpus.len(), len);
+            assert!(corpus.iter().any(|text| text == line));
+            assert!(corpus.entries().iter().all(|entry| entry.title.is_some()));
+        }
+
+        // 多音字按传统读法：不亦说（yuè）乎、知（zhì）者乐（yào）水
+        let lun_yu = Corpus::lun_yu();
+        let reading = |text: &str| {
+            let entry = lun_yu.entries().iter().find(|entry| entry.text == text).unwrap();
+            poetry_pass::transform::transform_entry(entry, &poetry_pass::Mode::PinyinFull)
+        };
+        assert_eq!(reading("不亦说乎"), "buyiyuehu");
+        assert_eq!(reading("知者乐水"), "zhizheyaoshui");
+        assert_eq!(reading("学而时习之"), "xueershixizhi");
+
+        // 与诗句、词语一样计入熵
+        let generator = Generator::new().lun_yu_only().no_number();
+        let expected = 2.0 * (Corpus::lun_yu().len() as f64).log2();
+        assert!(generator.entropy_bits() > 0.0);
+        assert!(generator.entropy_bits() <= expected + 1e-9);
+    }
+
+    #[test]
+    fn test_familiarity_tiers() {
+        use poetry_pass::Tier;
+
+        assert_eq!(Tier::of("一览众山小"), Tier::Famous);
+        assert_eq!(Tier::of("三径苦无资"), Tier::Obscure);
+        let tiers: Vec<_> = Corpus::poetry().entries().iter().map(|entry| entry.tier).collect();
+        for tier in Tier::ALL {
+            assert!(tiers.contains(&Some(tier)));
+        }
+        // 词语和成语至少是常见
+        assert!(Corpus::words().entries().iter().all(|entry| entry.tier <= Some(Tier::Common)));
+
+        let famous = Generator::new().poetry_poetry().max_tier(Tier::Famous);
+        for _ in 0..20 {
+            let (_, entries) = famous.generate_with_entries();
+            assert!(entries.iter().all(|entry| entry.tier == Some(Tier::Famous)));
+        }
+
+        // 熟悉度越受限，熵越低；加权介于两者之间
+        let all = Generator::new().poetry_poetry();
+        let weighted = all.clone().tier_weights(4.0, 2.0, 1.0);
+        assert!(famous.entropy_bits() < weighted.entropy_bits());
+        assert!(weighted.entropy_bits() < all.entropy_bits());
+        assert_eq!(famous.all_tiers().entropy_bits(), all.entropy_bits());
+    }
+}
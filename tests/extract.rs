@@ -0,0 +1,93 @@
+#![cfg(feature = "import")]
+
+#[cfg(test)]
+mod tests {
+    use poetry_pass::corpus::extract::{hsk_words, poetry_lines, LineOptions, WordOptions};
+
+    const POEMS: &str = r#"[
+        {"title": "静夜思", "author": "李白", "paragraphs": ["床前明月光，疑是地上霜。", "举头望明月，低头思故乡。"]},
+        {"paragraphs": ["床前明月光！白日依山尽黄河入海流？", "Hello，春眠不觉晓；"]}
+    ]"#;
+
+    const HSK: &str = r#"[
+        {"simplified": "花朵", "level": ["new-3", "old-4"]},
+        {"simplified": "电脑", "level": ["new-5"]},
+        {"simplified": "好", "level": ["new-1"]},
+        {"simplified": "一下儿", "level": ["old-2", "new-2"]}
+    ]"#;
+
+    #[test]
+    fn test_poetry_lines() {
+        let lines = poetry_lines(POEMS, &LineOptions::default()).unwrap();
+        // 去重、排序，过滤非汉字和超长的句子
+        assert_eq!(lines, ["举头望明月", "低头思故乡", "床前明月光", "春眠不觉晓", "疑是地上霜"]);
+    }
+
+    #[test]
+    fn test_poetry_line_limits() {
+        let options = LineOptions { min_chars: 10, max_chars: 10 };
+        let lines = poetry_lines(POEMS, &options).unwrap();
+        assert_eq!(lines, ["白日依山尽黄河入海流"]);
+    }
+
+    #[test]
+    fn test_hsk_words() {
+        let words = hsk_words(HSK, &WordOptions::default()).unwrap();
+        assert_eq!(words, ["一下儿", "花朵"]);
+
+        let options = WordOptions { max_level: 5, ..WordOptions::default() };
+        assert_eq!(hsk_words(HSK, &options).unwrap(), ["一下儿", "电脑", "花朵"]);
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        assert!(poetry_lines("{", &LineOptions::default()).is_err());
+    }
+}
+
+#[cfg(test)]
+mod chinese_poetry {
+    use poetry_pass::corpus::extract::{poem_lines, LineOptions};
+    use poetry_pass::{Corpus, Generator};
+    use std::fs;
+
+    #[test]
+    fn test_poem_lines_keep_metadata() {
+        let json = r#"[
+            {"author": "苏轼", "rhythmic": "水调歌头", "paragraphs": ["明月几时有？把酒问青天。"]},
+            {"title": "关雎", "chapter": "国风", "content": ["关关雎鸠，在河之洲。"]}
+        ]"#;
+        let lines = poem_lines(json, &LineOptions { min_chars: 4, max_chars: 5 }).unwrap();
+        let texts: Vec<&str> = lines.iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(texts, ["关关雎鸠", "在河之洲", "把酒问青天", "明月几时有"]);
+
+        let moon = lines.iter().find(|line| line.text == "明月几时有").unwrap();
+        assert_eq!(moon.author.as_deref(), Some("苏轼"));
+        assert_eq!(moon.title.as_deref(), Some("水调歌头"));
+        assert_eq!(lines[0].author, None);
+        assert_eq!(lines[0].title.as_deref(), Some("关雎"));
+    }
+
+    #[test]
+    fn test_corpus_from_chinese_poetry() {
+        let dir = std::env::temp_dir().join(format!("poetry-pass-json-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("poet.tang.0.json"),
+            r#"[{"author": "李白", "title": "静夜思", "paragraphs": ["床前明月光，疑是地上霜。"]}]"#,
+        ).unwrap();
+        fs::write(dir.join("authors.tang.json"), r#"[{"name": "李白", "desc": "字太白"}]"#).unwrap();
+
+        let corpus = Corpus::load(&dir).unwrap();
+        assert_eq!(corpus.len(), 2);
+        let entry = corpus.entry(0).unwrap();
+        assert_eq!(entry.text, "床前明月光");
+        assert_eq!(entry.author.as_deref(), Some("李白"));
+        assert_eq!(entry.title.as_deref(), Some("静夜思"));
+
+        let (_, entries) = Generator::new().corpus(corpus).generate_with_entries();
+        assert!(entries.iter().all(|entry| entry.title.as_deref() == Some("静夜思")));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -48,4 +48,342 @@ mod tests {
         // 来源应该是中文
         assert!(source.chars().any(|c| c as u32 > 127));
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_entropy_bits() {
+        use poetry_pass::Corpus;
+        
+        let corpus = Corpus::from_lines("test", ["春天", "夏天", "秋天", "冬天"]);
+        assert_eq!(corpus.entropy_bits(), 2.0);
+        
+        let generator = Generator::new().corpus(corpus.clone()).no_number().allow_repeats();
+        assert!((generator.entropy_bits() - 4.0).abs() < 1e-9);
+        
+        // 后半部分不能与前半部分相同，只剩三个选择
+        let distinct = Generator::new().corpus(corpus.clone()).no_number();
+        assert!((distinct.entropy_bits() - 2.0 - 3f64.log2()).abs() < 1e-9);
+        
+        // 数字增加约 13.1 位
+        let with_number = Generator::new().corpus(corpus.clone()).allow_repeats();
+        assert!((with_number.entropy_bits() - 4.0 - 13.1357).abs() < 1e-3);
+        
+        // 首字母相同的词只算一次："花朵"/"很多" 都是 hd
+        let colliding = Corpus::from_lines("test", ["花朵", "很多"]);
+        assert_eq!(Generator::new().corpus(colliding.clone()).no_number().allow_repeats().entropy_bits(), 2.0);
+        assert_eq!(Generator::new().corpus(colliding).no_number().allow_repeats().initials().entropy_bits(), 0.0);
+        
+        // 共用多数字的句子也不会相随：前两句之后只能接 "春风又绿"，
+        // "春风又绿" 之后还有两个选择
+        let sharing = Corpus::from_lines("test", ["明月几时有", "明月几时照", "春风又绿"]);
+        let generator = Generator::new().corpus(sharing).chinese().no_number();
+        assert!((generator.entropy_bits() - 3f64.log2() - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_union_and_weighted_probabilities() {
+        use poetry_pass::provider::Sampling;
+        use poetry_pass::{Corpus, Source};
+
+        let one = Source::Custom(Corpus::from_lines("one", ["花朵"]));
+        let three = Source::Custom(Corpus::from_lines("three", ["月亮", "星星", "春天里"]));
+        let probability = |source: &Source, sampling: &Sampling, text: &str| -> f64 {
+            source.front_candidates(sampling)
+                .iter()
+                .filter(|(entry, _)| entry.text == text)
+                .map(|&(_, p)| p)
+                .sum()
+        };
+        let all = Sampling::default();
+
+        // 并集：每个候选的概率相同
+        let union = Source::Union(vec![one.clone(), three.clone()]);
+        for text in ["花朵", "月亮", "星星", "春天里"] {
+            assert!((probability(&union, &all, text) - 0.25).abs() < 1e-12);
+        }
+        // 限制长度后，仍在剩下的候选中均匀抽取
+        let two_chars = Sampling { length: Some(2..=2), ..Sampling::default() };
+        assert!((probability(&union, &two_chars, "花朵") - 1.0 / 3.0).abs() < 1e-12);
+
+        // 加权：先按权重选来源，再在来源中均匀抽取
+        let weighted = Source::Weighted(vec![(one, 3.0), (three, 1.0)]);
+        assert!((probability(&weighted, &all, "花朵") - 0.75).abs() < 1e-12);
+        assert!((probability(&weighted, &all, "月亮") - 1.0 / 12.0).abs() < 1e-12);
+
+        // 熵与分布一致：四个等概率的候选是 2 位
+        let generator = Generator::new().chinese().no_number().allow_repeats().union(vec![
+            Source::Custom(Corpus::from_lines("one", ["花朵"])),
+            Source::Custom(Corpus::from_lines("three", ["月亮", "星星", "春天里"])),
+        ]);
+        assert!((generator.entropy_bits() - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_distinct_segments() {
+        use poetry_pass::Corpus;
+
+        // "花朵"/"很多" 首字母都是 hd，"花园" 与 "花朵" 只共用一半的字
+        let corpus = Corpus::from_lines("test", ["花朵", "很多", "花园"]);
+        let generator = Generator::new().corpus(corpus).initials().no_number();
+        for _ in 0..50 {
+            let password = generator.generate();
+            let parts: Vec<&str> = password.split('-').collect();
+            assert_ne!(parts[0], parts[1]);
+        }
+
+        // 与前半部分共用多数字的句子不会被选中
+        let corpus = Corpus::from_lines("test", ["明月几时有", "明月几时照", "春风又绿"]);
+        let generator = Generator::new().corpus(corpus).chinese().no_number();
+        for _ in 0..50 {
+            let (_, source) = generator.generate_with_source();
+            assert!(source.contains("春风又绿"));
+        }
+    }
+
+    #[test]
+    fn test_generate_unique_batch() {
+        use poetry_pass::{BatchError, BatchOptions, Corpus};
+        use std::collections::HashSet;
+
+        // 四个词、前后不同、无数字：最多 4 × 3 = 12 个密码
+        let corpus = Corpus::from_lines("test", ["春天", "夏天", "秋天", "冬天"]);
+        let generator = Generator::new().corpus(corpus.clone()).chinese().no_number();
+        assert_eq!(generator.batch_capacity(&BatchOptions::default()), 12);
+
+        let passwords = generator.generate_unique(12).unwrap();
+        assert_eq!(passwords.iter().collect::<HashSet<_>>().len(), 12);
+        assert_eq!(
+            generator.generate_unique(13),
+            Err(BatchError::TooMany { requested: 13, available: 12 })
+        );
+
+        // 只算能相随的前后两部分："明月几时有"/"明月几时照" 只能接 "春风又绿"
+        let sharing = Corpus::from_lines("test", ["明月几时有", "明月几时照", "春风又绿"]);
+        let generator = Generator::new().corpus(sharing).chinese().no_number();
+        assert_eq!(generator.batch_capacity(&BatchOptions::default()), 4);
+        assert_eq!(generator.generate_unique(4).unwrap().iter().collect::<HashSet<_>>().len(), 4);
+
+        // 首字母："花朵"/"很多" 都是 hd，只有 hd-hy 和 hy-hd
+        let initials = Corpus::from_lines("test", ["花朵", "很多", "花园"]);
+        let generator = Generator::new().corpus(initials.clone()).initials().no_number();
+        assert_eq!(generator.batch_capacity(&BatchOptions::default()), 2);
+
+        // 只有大小写不同的密码不算不同
+        let generator = Generator::new().corpus(initials).initials().no_number().random_capitalize();
+        let passwords = generator.generate_unique(2).unwrap();
+        assert_ne!(passwords[0].to_lowercase(), passwords[1].to_lowercase());
+        let generator = Generator::new().corpus(corpus).chinese().no_number();
+
+        // 每句最多用一次：四个词只够两个密码
+        let options = BatchOptions { distinct_lines: true };
+        let batch = generator.generate_batch(2, &options).unwrap();
+        let lines: HashSet<&str> = batch.iter()
+            .flat_map(|passphrase| passphrase.segments.iter().map(|segment| segment.entry.text.as_str()))
+            .collect();
+        assert_eq!(lines.len(), 4);
+        assert!(matches!(generator.generate_batch(3, &options), Err(BatchError::TooMany { .. })));
+
+        // 大批量也保证唯一
+        let passwords = Generator::new().generate_unique(2000).unwrap();
+        assert_eq!(passwords.iter().collect::<HashSet<_>>().len(), 2000);
+    }
+
+    #[test]
+    fn test_generate_passphrase() {
+        use poetry_pass::Mode;
+
+        let generator = Generator::new().front_full_back_init().separator("_");
+        let passphrase = generator.generate_passphrase();
+
+        // 不随机大写时，各部分加数字正好拼成密码
+        let mut parts: Vec<String> = passphrase.segments.iter().map(|segment| segment.text.clone()).collect();
+        parts.push(passphrase.number.unwrap().to_string());
+        assert_eq!(parts.join("_"), passphrase.password);
+        assert_eq!(passphrase.separator, "_");
+
+        assert_eq!(passphrase.segments[0].mode, Mode::PinyinFull);
+        assert_eq!(passphrase.segments[1].mode, Mode::PinyinInit);
+        assert_eq!(passphrase.mode_name(), "pinyin_full/pinyin_init");
+        assert_eq!(passphrase.source(), format!("{}_{}", passphrase.segments[0].entry.text, passphrase.segments[1].entry.text));
+        assert!((passphrase.entropy_bits - generator.entropy_bits()).abs() < 1e-9);
+
+        // 每部分记录来源词库和序号
+        for segment in &passphrase.segments {
+            let corpus = if segment.corpus == "words" { poetry_pass::Corpus::words() } else { poetry_pass::Corpus::poetry() };
+            assert_eq!(corpus.entry(segment.index), Some(&segment.entry));
+        }
+        assert_eq!(passphrase.to_string(), passphrase.password);
+
+        // 不加数字时没有 number
+        let passphrase = Generator::new().no_number().chinese().generate_passphrase();
+        assert_eq!(passphrase.number, None);
+        assert_eq!(passphrase.mode_name(), "chinese");
+        assert_eq!(passphrase.password, passphrase.source());
+    }
+
+    #[test]
+    fn test_typed_errors() {
+        use poetry_pass::{Corpus, Error};
+
+        // 默认设置可以通过检查
+        let generator = Generator::new().min_entropy(30.0).build().unwrap();
+        assert!(generator.try_generate().is_ok());
+
+        // 空词库不再 panic，而是返回错误
+        let empty = Generator::new().corpus(Corpus::from_lines("empty", Vec::<&str>::new()));
+        assert_eq!(empty.try_generate(), Err(Error::EmptyCorpus { corpora: "empty".to_string() }));
+        assert!(empty.build().is_err());
+
+        // 无法满足的长度限制和非法设置
+        let corpus = Corpus::from_lines("test", ["春天", "夏天", "秋天"]);
+        assert_eq!(
+            Generator::new().corpus(corpus.clone()).segment_length(5, 7).build().err(),
+            Some(Error::NoCandidates { corpora: "test".to_string() })
+        );
+        assert_eq!(
+            Generator::new().segment_length(7, 4).try_generate(),
+            Err(Error::InvalidLength { min: 7, max: 4 })
+        );
+        assert_eq!(
+            Generator::new().separator("a").build().err(),
+            Some(Error::InvalidSeparator("a".to_string()))
+        );
+        assert_eq!(Generator::new().tier_weights(0.0, 0.0, 0.0).build().err(), Some(Error::InvalidWeights));
+
+        // 只有一句时后半部分无法与前半部分不同
+        let single = Generator::new().corpus(Corpus::from_lines("one", ["明月"]));
+        assert!(matches!(single.clone().build(), Err(Error::NoDistinctBack { ref front, .. }) if front == "明月"));
+        assert!(single.allow_repeats().build().is_ok());
+
+        // 熵不足
+        let weak = Generator::new().corpus(corpus).no_number().min_entropy(100.0);
+        assert!(matches!(weak.build(), Err(Error::InsufficientEntropy { required, .. }) if required == 100.0));
+    }
+
+    #[test]
+    fn test_reroll_and_rerender() {
+        use poetry_pass::{Corpus, Mode};
+
+        let corpus = Corpus::from_lines("test", ["春天", "夏天", "秋风", "冬雪"]);
+        let generator = Generator::new().corpus(corpus).chinese();
+        let passphrase = generator.generate_passphrase();
+
+        // 只换后半部分：前半部分和数字不变，后半部分换成别的词
+        let rerolled = generator.reroll_segment(&passphrase, 1).unwrap();
+        assert_eq!(rerolled.segments[0], passphrase.segments[0]);
+        assert_ne!(rerolled.segments[1].entry.text, passphrase.segments[1].entry.text);
+        assert_ne!(rerolled.segments[1].entry.text, passphrase.segments[0].entry.text);
+        assert_eq!(rerolled.number, passphrase.number);
+
+        // 换模式后词和数字不变
+        let initials = generator.clone().initials().rerender(&rerolled);
+        assert_eq!(initials.source(), rerolled.source());
+        assert_eq!(initials.number, rerolled.number);
+        assert_eq!(initials.segments[0].mode, Mode::PinyinInit);
+        assert_eq!(initials.segments[0].text.len(), 2);
+    }
+
+    #[test]
+    fn test_reroll_number_and_capitals() {
+        let generator = Generator::new().random_capitalize();
+        let passphrase = generator.generate_passphrase();
+        assert!((1..=3).contains(&passphrase.capitals.len()));
+        for &i in &passphrase.capitals {
+            assert!(passphrase.password.chars().nth(i).unwrap().is_ascii_uppercase());
+        }
+
+        // 换数字：各部分和大写位置不变
+        let rerolled = generator.reroll_number(&passphrase);
+        assert_eq!(rerolled.segments, passphrase.segments);
+        assert_eq!(rerolled.capitals, passphrase.capitals);
+        assert!(rerolled.password.ends_with(&rerolled.number.unwrap().to_string()));
+
+        // 换大写：忽略大小写时密码不变
+        let rerolled = generator.reroll_capitalization(&passphrase);
+        assert_eq!(rerolled.password.to_lowercase(), passphrase.password.to_lowercase());
+        assert!(!rerolled.capitals.is_empty());
+
+        // 换前半部分：后半部分的大写保持不变，大写字母总数不变
+        let rerolled = generator.reroll_segment(&passphrase, 0).unwrap();
+        let back = |p: &poetry_pass::Passphrase| p.password.chars().skip(p.segment_range(1).start).collect::<String>();
+        assert_eq!(back(&rerolled), back(&passphrase));
+        assert_eq!(rerolled.capitals.len(), passphrase.capitals.len());
+    }
+
+    #[test]
+    fn test_render_in_other_modes() {
+        use poetry_pass::{Corpus, Entry, Error, Mode};
+
+        // 注音带声调，轻声在前面加 ˙
+        let corpus = Corpus::from_entries("test", [
+            Entry { pinyin: Some("zhōng guó".to_string()), ..Entry::new("中国") },
+            Entry { pinyin: Some("yuè liang".to_string()), ..Entry::new("月亮") },
+            Entry { pinyin: Some("jiǔ shuǐ".to_string()), ..Entry::new("酒水") },
+        ]);
+        let generator = Generator::new().corpus(corpus).initials().separator("_");
+        let passphrase = generator.restore(&[("test", 0), ("test", 1)], Some(1234)).unwrap();
+        assert_eq!(passphrase.password, "zg_yl_1234");
+        assert_eq!(passphrase.render_in(&Mode::Chinese), "中国_月亮_1234");
+        assert_eq!(passphrase.render_in(&Mode::PinyinFull), "zhongguo_yueliang_1234");
+        assert_eq!(passphrase.render_in(&Mode::Zhuyin), "ㄓㄨㄥㄍㄨㄛˊ_ㄩㄝˋ˙ㄌㄧㄤ_1234");
+
+        let passphrase = generator.restore(&[("test", 2), ("test", 0)], None).unwrap();
+        assert_eq!(passphrase.render_in(&Mode::Zhuyin), "ㄐㄧㄡˇㄕㄨㄟˇ_ㄓㄨㄥㄍㄨㄛˊ");
+        assert_eq!(
+            generator.restore(&[("test", 3), ("test", 0)], None),
+            Err(Error::UnknownEntry { corpus: "test".to_string(), index: 3 })
+        );
+
+        // 不带拼音的句子用 pinyin crate 的读音
+        let passphrase = Generator::new().zhuyin().no_number().poetry_only().generate_passphrase();
+        assert!(passphrase.password.chars().all(|c| ('\u{3100}'..='\u{312F}').contains(&c) || "ˊˇˋ˙-".contains(c)));
+    }
+
+    #[test]
+    fn test_backup_round_trip() {
+        use poetry_pass::{Corpus, Error, Source};
+
+        // 备份码和备份词都能还原出完全相同的密码，包括数字和大写
+        let generator = Generator::new()
+            .sources(Source::Chengyu, Source::Poetry)
+            .front_full_back_init()
+            .random_capitalize();
+        for _ in 0..20 {
+            let passphrase = generator.generate_passphrase();
+            let code = generator.backup_code(&passphrase).unwrap();
+            let words = generator.backup_words(&passphrase).unwrap();
+            assert!(code.split('-').all(|group| (1..=4).contains(&group.len())));
+            assert_eq!(generator.from_backup(&code).unwrap(), passphrase);
+            assert_eq!(generator.from_backup(&code.to_lowercase().replace('-', "")).unwrap(), passphrase);
+            assert_eq!(generator.from_backup(&words.replace('-', " ")).unwrap(), passphrase);
+        }
+
+        // 抄错一个字符会被校验和发现
+        let passphrase = generator.generate_passphrase();
+        let code = generator.backup_code(&passphrase).unwrap();
+        let typo: String = code.chars()
+            .enumerate()
+            .map(|(i, c)| match (i, c) {
+                (6, 'Z') => 'Y',
+                (6, _) => 'Z',
+                _ => c,
+            })
+            .collect();
+        let last = code.chars().last().unwrap();
+        let last_typo = format!("{}{}", &code[..code.len() - 1], if last == 'Z' { 'Y' } else { 'Z' });
+        assert_eq!(generator.from_backup(&last_typo), Err(Error::InvalidBackup));
+        assert_eq!(generator.from_backup(&typo), Err(Error::InvalidBackup));
+        assert_eq!(generator.from_backup("not a code"), Err(Error::InvalidBackup));
+
+        // 采样设置不影响还原，模式、分隔符和词库不同则报错
+        let sampled = generator.clone().segment_length(4, 5).no_number();
+        assert_eq!(sampled.from_backup(&code).unwrap().password, passphrase.password);
+        assert_eq!(generator.clone().separator("_").from_backup(&code), Err(Error::BackupMismatch));
+        assert_eq!(generator.clone().initials().from_backup(&code), Err(Error::BackupMismatch));
+        let corpus = Generator::new().corpus(Corpus::from_lines("test", ["春天", "夏天", "秋天"]));
+        assert_eq!(corpus.from_backup(&code), Err(Error::BackupMismatch));
+
+        // 不是这个生成器的句子无法备份
+        let other = corpus.generate_passphrase();
+        assert!(matches!(generator.backup_code(&other), Err(Error::UnknownEntry { .. })));
+    }
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "serde")]
+
+#[cfg(test)]
+mod tests {
+    use poetry_pass::{Corpus, Entry, Generator, Mode, Source, TransformMode, RECIPE_VERSION};
+
+    #[test]
+    fn test_mode_and_source_names() {
+        assert_eq!(serde_json::to_string(&Mode::PinyinInit).unwrap(), "\"pinyin_init\"");
+        assert_eq!(
+            serde_json::to_string(&TransformMode::Single(Mode::Chinese)).unwrap(),
+            r#"{"single":"chinese"}"#
+        );
+        assert_eq!(serde_json::to_string(&Source::SongCi).unwrap(), "\"song_ci\"");
+        assert_eq!(
+            serde_json::to_string(&Source::Pair(Box::new(Source::Chengyu), Box::new(Source::Poetry))).unwrap(),
+            r#"{"pair":["chengyu","poetry"]}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&Source::Weighted(vec![(Source::Poetry, 3.0), (Source::LunYu, 1.0)])).unwrap(),
+            r#"{"weighted":[["poetry",3.0],["lun_yu",1.0]]}"#
+        );
+    }
+
+    #[test]
+    fn test_generator_round_trip() {
+        let corpus = Corpus::from_entries("mine", [
+            Entry { author: Some("李白".to_string()), ..Entry::new("明月") },
+            Entry::new("清风"),
+        ]);
+        let generator = Generator::new()
+            .sources(Source::Custom(corpus), Source::Poetry)
+            .front_full_back_init()
+            .separator("_")
+            .no_number()
+            .segment_length(2, 7)
+            .tier_weights(4.0, 2.0, 1.0)
+            .allow_repeats();
+
+        let json = serde_json::to_string(&generator).unwrap();
+        let restored: Generator = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+        assert!(json.contains(r#""author":"李白""#));
+
+        // 还原后的生成器行为一致
+        assert!((restored.entropy_bits() - generator.entropy_bits()).abs() < 1e-9);
+        let passphrase = restored.generate_passphrase();
+        assert!(["明月", "清风"].contains(&passphrase.segments[0].entry.text.as_str()));
+        assert_eq!(passphrase.number, None);
+        assert!(passphrase.password.contains('_'));
+    }
+
+    #[test]
+    fn test_passphrase_round_trip() {
+        use poetry_pass::Passphrase;
+
+        let passphrase = Generator::new().random_capitalize().generate_passphrase();
+        let json = serde_json::to_string(&passphrase).unwrap();
+        assert!(json.contains(r#""corpus":"poetry""#));
+        let restored: Passphrase = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, passphrase);
+    }
+
+    #[test]
+    fn test_recipe_defaults_and_versions() {
+        // 只有 version 时与 Generator::new() 相同
+        let recipe = format!(r#"{{"version": {}}}"#, RECIPE_VERSION);
+        let generator: Generator = serde_json::from_str(&recipe).unwrap();
+        assert_eq!(
+            serde_json::to_string(&generator).unwrap(),
+            serde_json::to_string(&Generator::new()).unwrap()
+        );
+
+        let generator: Generator = serde_json::from_str(r#"{"version": 1, "source": "chengyu", "number": false}"#).unwrap();
+        assert_eq!(generator.generate_passphrase().segments[0].entry.char_count(), 4);
+
+        // 缺少版本、版本过新或未知字段都会报错
+        assert!(serde_json::from_str::<Generator>(r#"{"source": "poetry"}"#).is_err());
+        assert!(serde_json::from_str::<Generator>(r#"{"version": 99}"#).is_err());
+        assert!(serde_json::from_str::<Generator>(r#"{"version": 1, "seperator": "_"}"#).is_err());
+
+        let generator: Generator = serde_json::from_str(r#"{"version": 1, "min_entropy": 40.0}"#).unwrap();
+        assert!(serde_json::to_string(&generator).unwrap().contains(r#""min_entropy":40.0"#));
+
+        // 无论语料如何都无效的设置会被拒绝，而不是在生成时 panic
+        for recipe in [
+            r#"{"version": 1, "tier_weights": [0, 0, 0]}"#,
+            r#"{"version": 1, "tier_weights": [1, -1, 1]}"#,
+            r#"{"version": 1, "length": [7, 4]}"#,
+            r#"{"version": 1, "separator": "a"}"#,
+            r#"{"version": 1, "source": {"weighted": [["poetry", 0.0]]}}"#,
+        ] {
+            assert!(serde_json::from_str::<Generator>(recipe).is_err(), "{}", recipe);
+        }
+    }
+}
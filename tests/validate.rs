@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod tests {
+    use poetry_pass::corpus::validate::{normalize, validate};
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("　春眠不觉晓。"), "春眠不觉晓");
+        assert_eq!(normalize("春眠 不觉晓，"), "春眠不觉晓");
+        assert_eq!(normalize("ＡＢＣ"), "ABC");
+    }
+
+    #[test]
+    fn test_dedupe_and_reject() {
+        let report = validate(["# 注释", "春眠不觉晓", "春眠不觉晓。", "abc好", "", "处处闻啼鸟"]);
+        assert_eq!(report.lines, ["春眠不觉晓", "处处闻啼鸟"]);
+        assert_eq!(report.duplicates, ["春眠不觉晓"]);
+        assert_eq!(report.rejected.len(), 1);
+        assert_eq!(report.rejected[0].line_no, 4);
+        assert_eq!(report.rejected[0].ch, 'a');
+        assert!(!report.is_clean());
+    }
+
+    #[test]
+    fn test_initials_collisions() {
+        let report = validate(["花朵", "很多", "月亮"]);
+        assert_eq!(report.collisions.len(), 1);
+        assert_eq!(report.collisions[0].initials, "hd");
+        assert_eq!(report.collisions[0].lines, ["花朵", "很多"]);
+    }
+
+    #[test]
+    fn test_bundled_data_is_clean() {
+        let report = validate(poetry_pass::POETRY.iter().copied());
+        assert!(report.is_clean());
+        assert_eq!(report.lines.len(), poetry_pass::POETRY.len());
+    }
+}